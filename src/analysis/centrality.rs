@@ -0,0 +1,260 @@
+//! How central each node is, by several measures, to pick out hubs and bottlenecks
+
+use std::collections::VecDeque;
+use rand::{seq, Rng};
+use petgraph::Graph;
+use graph::{Node, Edge};
+use super::{neighbours, distances};
+
+/// Iterations of eigenvector centrality and PageRank, unless they settle sooner
+const ITERATIONS: usize = 100;
+/// Change between iterations small enough to stop at
+const TOLERANCE: f32 = 1e-6;
+/// Chance of PageRank's random walk following an edge rather than jumping
+pub const DAMPING: f32 = 0.85;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Centrality {
+    Degree,
+    Betweenness,
+    Closeness,
+    Eigenvector,
+    PageRank,
+}
+
+pub const ALL: [Centrality; 5] = [
+    Centrality::Degree,
+    Centrality::Betweenness,
+    Centrality::Closeness,
+    Centrality::Eigenvector,
+    Centrality::PageRank,
+];
+
+impl Centrality {
+    pub fn parse(name: &str) -> Option<Centrality> {
+        ALL.iter().cloned().find(|c| c.name() == name.to_lowercase())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Centrality::Degree => "degree",
+            Centrality::Betweenness => "betweenness",
+            Centrality::Closeness => "closeness",
+            Centrality::Eigenvector => "eigenvector",
+            Centrality::PageRank => "pagerank",
+        }
+    }
+
+    /// The measure for every node, by node index. Betweenness follows paths
+    /// from at most `samples` nodes chosen with `rng`.
+    pub fn of<R: Rng>(&self, graph: &Graph<Node, Edge>, samples: usize, rng: &mut R) -> Vec<f32> {
+        let adjacency = neighbours(graph);
+        match *self {
+            Centrality::Degree => degree(&adjacency),
+            Centrality::Betweenness => betweenness(&adjacency, samples, rng),
+            Centrality::Closeness => closeness(&adjacency),
+            Centrality::Eigenvector => eigenvector(&adjacency),
+            Centrality::PageRank => pagerank(&adjacency, DAMPING),
+        }
+    }
+}
+
+/// Values scaled so the largest is 1, for mapping to sizes and colours
+pub fn normalise(values: &[f32]) -> Vec<f32> {
+    let max = values.iter().cloned().fold(0., f32::max);
+    values.iter().map(|&v| if max > 0. { v / max } else { 0. }).collect()
+}
+
+/// Indices of the `count` highest values, highest first
+pub fn top(values: &[f32], count: usize) -> Vec<usize> {
+    let mut order = (0..values.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| values[b].partial_cmp(&values[a]).unwrap());
+    order.truncate(count);
+    order
+}
+
+/// Fraction of the other nodes each node interacts with
+pub fn degree(adjacency: &[Vec<usize>]) -> Vec<f32> {
+    let others = (adjacency.len().max(2) - 1) as f32;
+    adjacency.iter().map(|list| list.len() as f32 / others).collect()
+}
+
+/// Fraction of shortest paths between other nodes passing through each node,
+/// by Brandes' algorithm. With more nodes than `samples`, paths are followed
+/// from a random sample of them and the counts scaled up.
+pub fn betweenness<R: Rng>(adjacency: &[Vec<usize>], samples: usize, rng: &mut R) -> Vec<f32> {
+    let n = adjacency.len();
+    let mut centrality = vec![0.; n];
+    if n < 3 {
+        return centrality;
+    }
+    let sources = if n > samples { seq::sample_indices(rng, n, samples) } else { (0..n).collect() };
+
+    let mut order = Vec::with_capacity(n);
+    let mut queue = VecDeque::new();
+    let mut predecessors = vec![Vec::new(); n];
+    let mut paths = vec![0f64; n];
+    let mut distance = vec![usize::max_value(); n];
+    let mut dependency = vec![0f64; n];
+    for &s in &sources {
+        for v in 0..n {
+            predecessors[v].clear();
+            paths[v] = 0.;
+            distance[v] = usize::max_value();
+            dependency[v] = 0.;
+        }
+        paths[s] = 1.;
+        distance[s] = 0;
+        queue.push_back(s);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for &w in &adjacency[v] {
+                if distance[w] == usize::max_value() {
+                    distance[w] = distance[v] + 1;
+                    queue.push_back(w);
+                }
+                if distance[w] == distance[v] + 1 {
+                    paths[w] += paths[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+        // dependencies accumulate back from the furthest nodes
+        while let Some(w) = order.pop() {
+            for &v in &predecessors[w] {
+                dependency[v] += paths[v] / paths[w] * (1. + dependency[w]);
+            }
+            if w != s {
+                centrality[w] += dependency[w] as f32;
+            }
+        }
+    }
+
+    // each unordered pair was counted from both ends
+    let scale = n as f32 / sources.len() as f32 / ((n - 1) * (n - 2)) as f32;
+    centrality.iter().map(|&c| c * scale).collect()
+}
+
+/// Inverse mean distance to the nodes each one reaches, weighted by the
+/// fraction of the network it reaches so small components don't stand out
+pub fn closeness(adjacency: &[Vec<usize>]) -> Vec<f32> {
+    let n = adjacency.len();
+    (0..n).map(|v| {
+        let reached = distances(adjacency, v).into_iter().filter_map(|d| d).filter(|&d| d > 0).collect::<Vec<_>>();
+        let total: usize = reached.iter().sum();
+        if total == 0 {
+            return 0.;
+        }
+        let r = reached.len() as f32;
+        (r / (n - 1) as f32) * (r / total as f32)
+    }).collect()
+}
+
+/// Principal eigenvector of the adjacency matrix, by power iteration, with
+/// unit length. Being central means having central neighbours.
+pub fn eigenvector(adjacency: &[Vec<usize>]) -> Vec<f32> {
+    let n = adjacency.len();
+    let mut x = vec![1. / (n as f32).sqrt(); n];
+    for _ in 0..ITERATIONS {
+        // adding x itself shifts the spectrum, so bipartite networks converge too
+        let mut next = x.clone();
+        for v in 0..n {
+            for &w in &adjacency[v] {
+                next[v] += x[w];
+            }
+        }
+        let length = next.iter().map(|a| a * a).sum::<f32>().sqrt();
+        if length == 0. {
+            return next;
+        }
+        for a in &mut next {
+            *a /= length;
+        }
+        let change: f32 = next.iter().zip(&x).map(|(a, b)| (a - b).abs()).sum();
+        x = next;
+        if change < TOLERANCE * n as f32 {
+            break;
+        }
+    }
+    x
+}
+
+/// Stationary distribution of a random walk along interactions, which jumps
+/// to a random node with probability `1 - damping` or when stuck
+pub fn pagerank(adjacency: &[Vec<usize>], damping: f32) -> Vec<f32> {
+    let n = adjacency.len();
+    let mut rank = vec![1. / n as f32; n];
+    for _ in 0..ITERATIONS {
+        let stuck: f32 = (0..n).filter(|&v| adjacency[v].is_empty()).map(|v| rank[v]).sum();
+        let base = (1. - damping + damping * stuck) / n as f32;
+        let mut next = vec![base; n];
+        for v in 0..n {
+            let share = damping * rank[v] / adjacency[v].len().max(1) as f32;
+            for &w in &adjacency[v] {
+                next[w] += share;
+            }
+        }
+        let change: f32 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    rank
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, StdRng};
+    use graph::Network;
+
+    /// A star: node 0 joined to each of four leaves
+    fn star() -> Vec<Vec<usize>> {
+        let mut network = Network::new();
+        for leaf in &["l1", "l2", "l3", "l4"] {
+            network.add_edge("hub", "pp", leaf);
+        }
+        neighbours(&network.graph)
+    }
+
+    fn close(values: &[f32], expected: &[f32]) -> bool {
+        values.len() == expected.len() && values.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-4)
+    }
+
+    #[test]
+    fn star_degree_and_betweenness() {
+        let adjacency = star();
+        assert!(close(&degree(&adjacency), &[1., 0.25, 0.25, 0.25, 0.25]));
+        let mut rng: StdRng = SeedableRng::from_seed(&[1][..]);
+        // every path between two leaves goes through the hub
+        assert!(close(&betweenness(&adjacency, 200, &mut rng), &[1., 0., 0., 0., 0.]));
+    }
+
+    #[test]
+    fn star_closeness() {
+        // a leaf is 1 hop from the hub and 2 from each other leaf
+        let leaf = 4. / 7.;
+        assert!(close(&closeness(&star()), &[1., leaf, leaf, leaf, leaf]));
+    }
+
+    #[test]
+    fn star_eigenvector_and_pagerank() {
+        let adjacency = star();
+        let x = eigenvector(&adjacency);
+        assert!((x.iter().map(|a| a * a).sum::<f32>() - 1.).abs() < 1e-4);
+        assert_eq!(top(&x, 1), vec![0]);
+        assert!(close(&x[1..], &[x[1]; 4]));
+
+        let rank = pagerank(&adjacency, DAMPING);
+        assert!((rank.iter().sum::<f32>() - 1.).abs() < 1e-4);
+        assert_eq!(top(&rank, 1), vec![0]);
+        assert!(close(&rank[1..], &[rank[1]; 4]));
+    }
+
+    #[test]
+    fn normalise_scales_to_the_largest() {
+        assert_eq!(normalise(&[2., 1., 0.]), vec![1., 0.5, 0.]);
+        assert_eq!(normalise(&[0., 0.]), vec![0., 0.]);
+    }
+}
@@ -0,0 +1,347 @@
+//! Communities, groups of nodes interacting more among themselves than with
+//! the rest, found by maximising modularity
+
+use std::collections::HashMap;
+use rand::Rng;
+use petgraph::Graph;
+use graph::{Node, Edge};
+use super::neighbours;
+
+/// Most rounds of moving nodes and merging communities
+const LEVELS: usize = 32;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Method {
+    Louvain,
+    /// Louvain with a refinement which keeps every community connected
+    Leiden,
+}
+
+impl Method {
+    pub fn parse(name: &str) -> Option<Method> {
+        match name.to_lowercase().as_str() {
+            "louvain" => Some(Method::Louvain),
+            "leiden" => Some(Method::Leiden),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Method::Louvain => "louvain",
+            Method::Leiden => "leiden",
+        }
+    }
+}
+
+/// Undirected graph with weighted edges, each merged node standing for a
+/// group of the original nodes
+struct Weighted {
+    /// Neighbours of every node with the weight of the edges to them
+    edges: Vec<Vec<(usize, f64)>>,
+    /// Weight of the edges inside every node, each counted once
+    loops: Vec<f64>,
+}
+
+impl Weighted {
+    fn of(graph: &Graph<Node, Edge>) -> Weighted {
+        let adjacency = neighbours(graph);
+        Weighted {
+            loops: vec![0.; adjacency.len()],
+            edges: adjacency.into_iter().map(|list| list.into_iter().map(|w| (w, 1.)).collect()).collect(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    fn degree(&self, v: usize) -> f64 {
+        self.edges[v].iter().map(|e| e.1).sum::<f64>() + 2. * self.loops[v]
+    }
+
+    /// One node for each of `count` parts, given by node
+    fn aggregate(&self, parts: &[usize], count: usize) -> Weighted {
+        let mut edges = vec![HashMap::new(); count];
+        let mut loops = vec![0.; count];
+        for v in 0..self.len() {
+            let p = parts[v];
+            loops[p] += self.loops[v];
+            for &(w, weight) in &self.edges[v] {
+                let q = parts[w];
+                if p == q {
+                    // seen from both ends
+                    loops[p] += weight / 2.;
+                } else {
+                    *edges[p].entry(q).or_insert(0.) += weight;
+                }
+            }
+        }
+        Weighted {
+            edges: edges.into_iter().map(|e| {
+                let mut list = e.into_iter().collect::<Vec<_>>();
+                list.sort_by_key(|&(q, _)| q);
+                list
+            }).collect(),
+            loops: loops,
+        }
+    }
+}
+
+/// Moves nodes one at a time to the neighbouring community improving
+/// modularity most, until none improves it. Returns whether any moved.
+fn move_nodes<R: Rng>(g: &Weighted, community: &mut [usize], resolution: f64, rng: &mut R) -> bool {
+    let n = g.len();
+    let degrees = (0..n).map(|v| g.degree(v)).collect::<Vec<_>>();
+    let total: f64 = degrees.iter().sum();
+    if total == 0. {
+        return false;
+    }
+    let mut totals = vec![0.; n];
+    for v in 0..n {
+        totals[community[v]] += degrees[v];
+    }
+
+    let mut order = (0..n).collect::<Vec<_>>();
+    rng.shuffle(&mut order);
+    let mut links = vec![0.; n];
+    let mut touched = Vec::new();
+    let mut moved = false;
+    loop {
+        let mut moved_any = false;
+        for &v in &order {
+            let own = community[v];
+            for &(w, weight) in &g.edges[v] {
+                let c = community[w];
+                if links[c] == 0. {
+                    touched.push(c);
+                }
+                links[c] += weight;
+            }
+            totals[own] -= degrees[v];
+
+            let gain = |c: usize, links: &[f64]| links[c] - resolution * totals[c] * degrees[v] / total;
+            let mut best = own;
+            let mut best_gain = gain(own, &links);
+            for &c in &touched {
+                let candidate = gain(c, &links);
+                if candidate > best_gain + 1e-12 {
+                    best = c;
+                    best_gain = candidate;
+                }
+            }
+
+            totals[best] += degrees[v];
+            community[v] = best;
+            if best != own {
+                moved_any = true;
+            }
+            for c in touched.drain(..) {
+                links[c] = 0.;
+            }
+        }
+        if !moved_any {
+            return moved;
+        }
+        moved = true;
+    }
+}
+
+/// Splits each community into well connected parts, starting from single
+/// nodes and merging them greedily, as Leiden's refinement does
+fn refine<R: Rng>(g: &Weighted, community: &[usize], resolution: f64, rng: &mut R) -> Vec<usize> {
+    let n = g.len();
+    let degrees = (0..n).map(|v| g.degree(v)).collect::<Vec<_>>();
+    let total: f64 = degrees.iter().sum();
+    let mut community_totals = vec![0.; n];
+    for v in 0..n {
+        community_totals[community[v]] += degrees[v];
+    }
+
+    let mut part = (0..n).collect::<Vec<_>>();
+    let mut size = vec![1; n];
+    let mut totals = degrees.clone();
+    // weight from each part to the rest of its community
+    let mut external = (0..n).map(|v| {
+        g.edges[v].iter().filter(|&&(w, _)| community[w] == community[v]).map(|e| e.1).sum::<f64>()
+    }).collect::<Vec<f64>>();
+    if total == 0. {
+        return part;
+    }
+    let connected = |external: f64, part_total: f64, community_total: f64| {
+        external >= resolution * part_total * (community_total - part_total) / total
+    };
+
+    let mut order = (0..n).collect::<Vec<_>>();
+    rng.shuffle(&mut order);
+    let mut links = vec![0.; n];
+    let mut touched = Vec::new();
+    for &v in &order {
+        let c = community[v];
+        if size[part[v]] > 1 || !connected(external[v], degrees[v], community_totals[c]) {
+            continue;
+        }
+        for &(w, weight) in &g.edges[v] {
+            if community[w] == c && part[w] != part[v] {
+                if links[part[w]] == 0. {
+                    touched.push(part[w]);
+                }
+                links[part[w]] += weight;
+            }
+        }
+
+        let mut best = None;
+        let mut best_gain = 0.;
+        for &p in &touched {
+            if !connected(external[p], totals[p], community_totals[c]) {
+                continue;
+            }
+            let gain = links[p] - resolution * degrees[v] * totals[p] / total;
+            if gain > best_gain {
+                best = Some(p);
+                best_gain = gain;
+            }
+        }
+        if let Some(p) = best {
+            // the edges between v and p are inside the merged part now
+            external[p] += external[v] - 2. * links[p];
+            totals[p] += degrees[v];
+            size[p] += 1;
+            size[v] -= 1;
+            part[v] = p;
+        }
+        for p in touched.drain(..) {
+            links[p] = 0.;
+        }
+    }
+    part
+}
+
+/// Numbers the groups of a partition from 0, returning how many there are
+fn renumber(partition: &mut [usize]) -> usize {
+    let mut numbers = HashMap::new();
+    for c in partition.iter_mut() {
+        let next = numbers.len();
+        *c = *numbers.entry(*c).or_insert(next);
+    }
+    numbers.len()
+}
+
+/// Community of every node, by node index, numbered from the largest.
+///
+/// Higher `resolution` finds more and smaller communities, 1 being standard
+/// modularity. Nodes are visited in an order shuffled with `rng`.
+pub fn detect<R: Rng>(graph: &Graph<Node, Edge>, method: Method, resolution: f32, rng: &mut R) -> Vec<usize> {
+    let resolution = resolution as f64;
+    let mut g = Weighted::of(graph);
+    // the merged node each original node belongs to
+    let mut merged = (0..g.len()).collect::<Vec<_>>();
+    let mut community = (0..g.len()).collect::<Vec<_>>();
+
+    for _ in 0..LEVELS {
+        move_nodes(&g, &mut community, resolution, rng);
+        let count = renumber(&mut community);
+        if count == g.len() {
+            break;
+        }
+        let mut parts = match method {
+            Method::Louvain => community.clone(),
+            Method::Leiden => refine(&g, &community, resolution, rng),
+        };
+        let parts_count = renumber(&mut parts);
+        if parts_count == g.len() {
+            break;
+        }
+
+        // Leiden starts the merged nodes in their unrefined communities
+        let mut next = vec![0; parts_count];
+        for v in 0..g.len() {
+            next[parts[v]] = match method {
+                Method::Louvain => parts[v],
+                Method::Leiden => community[v],
+            };
+        }
+        g = g.aggregate(&parts, parts_count);
+        for m in merged.iter_mut() {
+            *m = parts[*m];
+        }
+        community = next;
+    }
+
+    let mut result = merged.iter().map(|&m| community[m]).collect::<Vec<_>>();
+    let count = renumber(&mut result);
+    let mut sizes = vec![0; count];
+    for &c in &result {
+        sizes[c] += 1;
+    }
+    let mut order = (0..count).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| sizes[b].cmp(&sizes[a]).then(a.cmp(&b)));
+    let mut rank = vec![0; count];
+    for (i, &c) in order.iter().enumerate() {
+        rank[c] = i;
+    }
+    result.iter().map(|&c| rank[c]).collect()
+}
+
+/// Modularity of a partition at the given resolution, from -1/2 to 1
+pub fn modularity(graph: &Graph<Node, Edge>, community: &[usize], resolution: f32) -> f32 {
+    let adjacency = neighbours(graph);
+    let total = adjacency.iter().map(|list| list.len()).sum::<usize>() as f64;
+    if total == 0. {
+        return 0.;
+    }
+    let count = community.iter().map(|&c| c + 1).max().unwrap_or(0);
+    let mut inside = vec![0.; count];
+    let mut totals = vec![0.; count];
+    for (v, list) in adjacency.iter().enumerate() {
+        totals[community[v]] += list.len() as f64;
+        inside[community[v]] += list.iter().filter(|&&w| community[w] == community[v]).count() as f64;
+    }
+    (0..count).map(|c| inside[c] / total - resolution as f64 * (totals[c] / total).powi(2)).sum::<f64>() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, StdRng};
+    use graph::Network;
+
+    /// Two 5-cliques, a0-a4 and b0-b4, joined by the edge a0-b0
+    fn cliques_and_bridge() -> Network {
+        let mut network = Network::new();
+        for side in &["a", "b"] {
+            for i in 0..5 {
+                for j in i + 1..5 {
+                    network.add_edge(&format!("{}{}", side, i), "pp", &format!("{}{}", side, j));
+                }
+            }
+        }
+        network.add_edge("a0", "pp", "b0");
+        network
+    }
+
+    #[test]
+    fn cliques_joined_by_a_bridge_are_two_communities() {
+        let ref graph = cliques_and_bridge().graph;
+        for &method in &[Method::Louvain, Method::Leiden] {
+            for seed in 0..5 {
+                let mut rng: StdRng = SeedableRng::from_seed(&[seed][..]);
+                let found = detect(graph, method, 1., &mut rng);
+                let (a, b) = (found[0], found[5]);
+                assert!(a != b, "{} with seed {}: {:?}", method.name(), seed, found);
+                assert_eq!(&found[..5], &[a; 5][..]);
+                assert_eq!(&found[5..], &[b; 5][..]);
+            }
+        }
+    }
+
+    #[test]
+    fn modularity_of_the_two_cliques() {
+        let ref graph = cliques_and_bridge().graph;
+        let split = [0, 0, 0, 0, 0, 1, 1, 1, 1, 1];
+        // each side has 20 of the 42 edge ends inside it and 21 in all
+        let expected = 2. * (20. / 42. - (21f32 / 42.).powi(2));
+        assert!((modularity(graph, &split, 1.) - expected).abs() < 1e-6);
+        assert_eq!(modularity(graph, &[0; 10], 1.), 0.);
+    }
+}
@@ -0,0 +1,135 @@
+//! k-cores, the largest parts of a network in which every node has at least
+//! k neighbours, for peeling a dense network down to its centre
+
+use petgraph::Graph;
+use graph::{Node, Edge};
+use super::neighbours;
+
+/// Largest k for which each node is in the k-core, by node index, found by
+/// Batagelj and Zaversnik's algorithm in time linear in the edges
+pub fn core_numbers(graph: &Graph<Node, Edge>) -> Vec<usize> {
+    let adjacency = neighbours(graph);
+    let n = adjacency.len();
+    let mut degree = adjacency.iter().map(|list| list.len()).collect::<Vec<_>>();
+    let max = degree.iter().cloned().max().unwrap_or(0);
+
+    // nodes sorted by degree, with where each degree starts
+    let mut start = vec![0; max + 1];
+    for &d in &degree {
+        start[d] += 1;
+    }
+    let mut total = 0;
+    for s in start.iter_mut() {
+        let count = *s;
+        *s = total;
+        total += count;
+    }
+    let mut position = vec![0; n];
+    let mut order = vec![0; n];
+    {
+        let mut next = start.clone();
+        for v in 0..n {
+            position[v] = next[degree[v]];
+            order[position[v]] = v;
+            next[degree[v]] += 1;
+        }
+    }
+
+    for i in 0..n {
+        let v = order[i];
+        for &u in &adjacency[v] {
+            if degree[u] > degree[v] {
+                // move u to the front of its degree's block, then shrink the block
+                let d = degree[u];
+                let first = order[start[d]];
+                if u != first {
+                    order.swap(position[u], start[d]);
+                    position[first] = position[u];
+                    position[u] = start[d];
+                }
+                start[d] += 1;
+                degree[u] -= 1;
+            }
+        }
+    }
+    degree
+}
+
+/// Which nodes to show: those in a k-core and with enough neighbours
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    /// The k-core to keep, 0 keeping everything
+    pub core: usize,
+    /// Fewest distinct neighbours a node may have
+    pub degree: usize,
+}
+
+impl Filter {
+    /// The deepest k-core and the most neighbours of any node, beyond which
+    /// a filter leaves nothing
+    pub fn limits(graph: &Graph<Node, Edge>) -> Filter {
+        Filter {
+            core: core_numbers(graph).into_iter().max().unwrap_or(0),
+            degree: neighbours(graph).iter().map(|list| list.len()).max().unwrap_or(0),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.core > 0 || self.degree > 0
+    }
+
+    /// Nodes passing the filter, by node index
+    pub fn apply(&self, graph: &Graph<Node, Edge>) -> Vec<bool> {
+        let cores = if self.core > 0 { core_numbers(graph) } else { vec![0; graph.node_count()] };
+        let adjacency = neighbours(graph);
+        (0..graph.node_count()).map(|v| cores[v] >= self.core && adjacency[v].len() >= self.degree).collect()
+    }
+
+    pub fn describe(&self) -> String {
+        match (self.core, self.degree) {
+            (0, 0) => "all nodes".to_string(),
+            (k, 0) => format!("the {}-core", k),
+            (0, d) => format!("nodes with at least {} neighbours", d),
+            (k, d) => format!("the {}-core, nodes with at least {} neighbours", k, d),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::Network;
+
+    /// A 4-clique a-d with a tail d-e-f, and a lone g
+    fn clique_with_tail() -> Network {
+        let mut network = Network::new();
+        for &(a, b) in &[("a", "b"), ("a", "c"), ("a", "d"), ("b", "c"), ("b", "d"), ("c", "d"), ("d", "e"), ("e", "f")] {
+            network.add_edge(a, "pp", b);
+        }
+        network.node("g");
+        network
+    }
+
+    #[test]
+    fn core_numbers_of_a_clique_with_a_tail() {
+        assert_eq!(core_numbers(&clique_with_tail().graph), vec![3, 3, 3, 3, 1, 1, 0]);
+    }
+
+    #[test]
+    fn parallel_edges_and_loops_add_nothing() {
+        let mut network = clique_with_tail();
+        network.add_edge("e", "pd", "f");
+        network.add_edge("f", "pp", "f");
+        assert_eq!(core_numbers(&network.graph), vec![3, 3, 3, 3, 1, 1, 0]);
+    }
+
+    #[test]
+    fn filter_and_its_limits() {
+        let ref graph = clique_with_tail().graph;
+        assert_eq!(Filter::limits(graph), Filter { core: 3, degree: 4 });
+        let core = Filter { core: 3, degree: 0 };
+        assert_eq!(core.apply(graph), vec![true, true, true, true, false, false, false]);
+        let degree = Filter { core: 0, degree: 2 };
+        assert_eq!(degree.apply(graph), vec![true, true, true, true, true, false, false]);
+    }
+}
@@ -0,0 +1,88 @@
+//! Ego networks, the nodes within a few hops of some chosen ones, for
+//! exploring a large network a neighbourhood at a time
+
+use petgraph::Graph;
+use petgraph::graph::NodeIndex;
+use graph::{Node, Edge};
+use super::neighbours;
+
+/// Nodes shown around, each with how many hops out from it are shown too
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ego {
+    centres: Vec<(NodeIndex, usize)>,
+}
+
+impl Ego {
+    /// The `hops` neighbourhood of the seeds
+    pub fn new(seeds: &[NodeIndex], hops: usize) -> Ego {
+        let mut ego = Ego { centres: Vec::new() };
+        for &v in seeds {
+            if ego.hops(v).is_none() {
+                ego.centres.push((v, hops));
+            }
+        }
+        ego
+    }
+
+    /// Hops shown around a node, if it is a centre
+    pub fn hops(&self, v: NodeIndex) -> Option<usize> {
+        self.centres.iter().find(|c| c.0 == v).map(|c| c.1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.centres.is_empty()
+    }
+
+    /// Shows one more hop around a node, making it a centre if it isn't one
+    pub fn expand(&mut self, v: NodeIndex) {
+        match self.centres.iter_mut().find(|c| c.0 == v) {
+            Some(centre) => centre.1 += 1,
+            None => self.centres.push((v, 1)),
+        }
+    }
+
+    /// Shows one hop less around a node, and stops centring on it once only
+    /// the node itself was shown. Returns whether it was a centre.
+    pub fn collapse(&mut self, v: NodeIndex) -> bool {
+        match self.centres.iter().position(|c| c.0 == v) {
+            Some(i) if self.centres[i].1 == 0 => {
+                self.centres.remove(i);
+                true
+            },
+            Some(i) => {
+                self.centres[i].1 -= 1;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Nodes within reach of a centre, by node index
+    pub fn apply(&self, graph: &Graph<Node, Edge>) -> Vec<bool> {
+        let adjacency = neighbours(graph);
+        // hops left on reaching each node, searched from the furthest reaching
+        let mut left: Vec<Option<usize>> = vec![None; adjacency.len()];
+        let most = self.centres.iter().map(|c| c.1).max().unwrap_or(0);
+        let mut levels = vec![Vec::new(); most + 1];
+        for &(v, hops) in &self.centres {
+            levels[hops].push(v.index());
+        }
+        for hops in (0..most + 1).rev() {
+            let level = ::std::mem::replace(&mut levels[hops], Vec::new());
+            for v in level {
+                if left[v].map_or(false, |l| l >= hops) {
+                    continue;
+                }
+                left[v] = Some(hops);
+                if hops > 0 {
+                    for &w in &adjacency[v] {
+                        if left[w].map_or(true, |l| l < hops - 1) {
+                            levels[hops - 1].push(w);
+                        }
+                    }
+                }
+            }
+        }
+        left.iter().map(|l| l.is_some()).collect()
+    }
+}
@@ -0,0 +1,82 @@
+//! Measures of a network's structure.
+//!
+//! Interactions are treated as undirected, and self-loops and parallel edges
+//! as a single connection, unless a measure says otherwise.
+
+use std::collections::VecDeque;
+use petgraph::Graph;
+use graph::{Node, Edge};
+
+pub mod statistics;
+pub mod centrality;
+pub mod community;
+pub mod paths;
+pub mod cores;
+pub mod ego;
+
+pub use self::statistics::Statistics;
+pub use self::centrality::Centrality;
+
+/// Most nodes path lengths are measured from. Larger networks are sampled,
+/// which keeps the cost linear in their size.
+pub const SAMPLES: usize = 200;
+
+/// Distinct neighbours of every node, by node index
+pub fn neighbours(graph: &Graph<Node, Edge>) -> Vec<Vec<usize>> {
+    let mut adjacency = vec![Vec::new(); graph.node_count()];
+    for edge in graph.raw_edges() {
+        let (a, b) = (edge.source().index(), edge.target().index());
+        if a != b {
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        }
+    }
+    for list in &mut adjacency {
+        list.sort();
+        list.dedup();
+    }
+    adjacency
+}
+
+/// Hops from `source` to every node, `None` for those it can't reach
+pub fn distances(adjacency: &[Vec<usize>], source: usize) -> Vec<Option<usize>> {
+    let mut distance = vec![None; adjacency.len()];
+    let mut queue = VecDeque::new();
+    distance[source] = Some(0);
+    queue.push_back(source);
+    while let Some(v) = queue.pop_front() {
+        let next = distance[v].unwrap() + 1;
+        for &w in &adjacency[v] {
+            if distance[w].is_none() {
+                distance[w] = Some(next);
+                queue.push_back(w);
+            }
+        }
+    }
+    distance
+}
+
+/// Connected component of every node, numbered from 0 in order of each
+/// component's first node
+pub fn components(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let mut component = vec![usize::max_value(); adjacency.len()];
+    let mut count = 0;
+    let mut stack = Vec::new();
+    for start in 0..adjacency.len() {
+        if component[start] != usize::max_value() {
+            continue;
+        }
+        component[start] = count;
+        stack.push(start);
+        while let Some(v) = stack.pop() {
+            for &w in &adjacency[v] {
+                if component[w] == usize::max_value() {
+                    component[w] = count;
+                    stack.push(w);
+                }
+            }
+        }
+        count += 1;
+    }
+    component
+}
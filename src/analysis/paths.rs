@@ -0,0 +1,235 @@
+//! Shortest paths between two nodes, by hops or by interaction weights
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use petgraph::Graph;
+use petgraph::graph::{NodeIndex, EdgeIndex};
+use graph::{Node, Edge};
+
+/// A walk between two nodes without repeating any of them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    pub nodes: Vec<NodeIndex>,
+    /// The edge taken between each pair of consecutive nodes
+    pub edges: Vec<EdgeIndex>,
+    /// Sum of the edges' weights, or the number of hops
+    pub cost: f32,
+}
+
+/// Every node's edges, ignoring direction: (neighbour, edge index, weight).
+/// Self-loops never shorten a path and are left out.
+fn adjacency<F: Fn(&Edge) -> f32>(graph: &Graph<Node, Edge>, weight: F) -> Vec<Vec<(usize, usize, f32)>> {
+    let mut adjacency = vec![Vec::new(); graph.node_count()];
+    for (i, edge) in graph.raw_edges().iter().enumerate() {
+        let (a, b) = (edge.source().index(), edge.target().index());
+        if a != b {
+            let w = weight(&edge.weight);
+            adjacency[a].push((b, i, w));
+            adjacency[b].push((a, i, w));
+        }
+    }
+    adjacency
+}
+
+/// Follows `previous` links back from `to`
+fn trace(previous: &[Option<(usize, usize)>], from: usize, to: usize, cost: f32) -> Path {
+    let mut nodes = vec![NodeIndex::new(to)];
+    let mut edges = Vec::new();
+    let mut v = to;
+    while v != from {
+        let (u, e) = previous[v].unwrap();
+        nodes.push(NodeIndex::new(u));
+        edges.push(EdgeIndex::new(e));
+        v = u;
+    }
+    nodes.reverse();
+    edges.reverse();
+    Path { nodes: nodes, edges: edges, cost: cost }
+}
+
+/// Fewest hops from `from` to `to`, by breadth-first search
+pub fn shortest(graph: &Graph<Node, Edge>, from: NodeIndex, to: NodeIndex) -> Option<Path> {
+    let adjacency = adjacency(graph, |_| 1.);
+    let (from, to) = (from.index(), to.index());
+    let mut previous = vec![None; adjacency.len()];
+    let mut seen = vec![false; adjacency.len()];
+    let mut queue = VecDeque::new();
+    seen[from] = true;
+    queue.push_back((from, 0));
+    while let Some((v, hops)) = queue.pop_front() {
+        if v == to {
+            return Some(trace(&previous, from, to, hops as f32));
+        }
+        for &(w, e, _) in &adjacency[v] {
+            if !seen[w] {
+                seen[w] = true;
+                previous[w] = Some((v, e));
+                queue.push_back((w, hops + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Entry of Dijkstra's queue, ordered so the cheapest comes out first
+#[derive(Copy, Clone, PartialEq)]
+struct Candidate {
+    cost: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal).then(other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Dijkstra's algorithm avoiding some nodes and edges, as Yen's needs
+fn dijkstra(adjacency: &[Vec<(usize, usize, f32)>], from: usize, to: usize,
+            blocked_nodes: &[bool], blocked_edges: &HashSet<usize>) -> Option<Path> {
+    let mut cost = vec![::std::f32::INFINITY; adjacency.len()];
+    let mut previous = vec![None; adjacency.len()];
+    let mut heap = BinaryHeap::new();
+    cost[from] = 0.;
+    heap.push(Candidate { cost: 0., node: from });
+    while let Some(Candidate { cost: c, node: v }) = heap.pop() {
+        if v == to {
+            return Some(trace(&previous, from, to, c));
+        }
+        if c > cost[v] {
+            continue;
+        }
+        for &(w, e, weight) in &adjacency[v] {
+            if blocked_nodes[w] || blocked_edges.contains(&e) {
+                continue;
+            }
+            let next = c + weight;
+            if next < cost[w] {
+                cost[w] = next;
+                previous[w] = Some((v, e));
+                heap.push(Candidate { cost: next, node: w });
+            }
+        }
+    }
+    None
+}
+
+/// Cheapest path from `from` to `to`, each edge costing `weight`, which
+/// must be positive
+pub fn cheapest<F: Fn(&Edge) -> f32>(graph: &Graph<Node, Edge>, from: NodeIndex, to: NodeIndex, weight: F) -> Option<Path> {
+    let adjacency = adjacency(graph, weight);
+    dijkstra(&adjacency, from.index(), to.index(), &vec![false; adjacency.len()], &HashSet::new())
+}
+
+/// Up to `k` cheapest paths, cheapest first, by Yen's algorithm. Paths
+/// through the same nodes along different parallel edges count separately.
+pub fn k_cheapest<F: Fn(&Edge) -> f32>(graph: &Graph<Node, Edge>, from: NodeIndex, to: NodeIndex, k: usize, weight: F) -> Vec<Path> {
+    let weights = graph.raw_edges().iter().map(|e| weight(&e.weight)).collect::<Vec<_>>();
+    let adjacency = adjacency(graph, &weight);
+    let mut blocked_nodes = vec![false; adjacency.len()];
+    let mut found: Vec<Path> = match dijkstra(&adjacency, from.index(), to.index(), &blocked_nodes, &HashSet::new()) {
+        Some(path) => vec![path],
+        None => return Vec::new(),
+    };
+    let mut candidates: Vec<Path> = Vec::new();
+
+    while found.len() < k {
+        let last = found.last().unwrap().clone();
+        // branch off the last path found at each of its nodes in turn
+        for i in 0..last.edges.len() {
+            let root = &last.nodes[..i + 1];
+            let mut blocked_edges = HashSet::new();
+            for path in &found {
+                if path.nodes.len() > i + 1 && &path.nodes[..i + 1] == root {
+                    blocked_edges.insert(path.edges[i].index());
+                }
+            }
+            for v in &root[..i] {
+                blocked_nodes[v.index()] = true;
+            }
+
+            if let Some(spur) = dijkstra(&adjacency, root[i].index(), to.index(), &blocked_nodes, &blocked_edges) {
+                let root_cost: f32 = last.edges[..i].iter().map(|e| weights[e.index()]).sum();
+                let mut nodes = root[..i].to_vec();
+                nodes.extend(spur.nodes);
+                let mut edges = last.edges[..i].to_vec();
+                edges.extend(spur.edges);
+                // the same route may come out with another rounding of its cost
+                let known = |path: &Path| path.edges == edges;
+                if !found.iter().any(&known) && !candidates.iter().any(&known) {
+                    candidates.push(Path { nodes: nodes, edges: edges, cost: root_cost + spur.cost });
+                }
+            }
+            for v in &root[..i] {
+                blocked_nodes[v.index()] = false;
+            }
+        }
+
+        if candidates.is_empty() {
+            break;
+        }
+        let best = (0..candidates.len()).min_by(|&a, &b| {
+            let (a, b) = (&candidates[a], &candidates[b]);
+            a.cost.partial_cmp(&b.cost).unwrap_or(Ordering::Equal)
+                .then(a.edges.len().cmp(&b.edges.len()))
+                .then_with(|| a.edges.cmp(&b.edges))
+        }).unwrap();
+        found.push(candidates.swap_remove(best));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graph::Network;
+
+    /// Edges weighted by their interaction, read as a number
+    fn weight(edge: &Edge) -> f32 {
+        edge.interaction.parse().unwrap()
+    }
+
+    #[test]
+    fn k_cheapest_finds_equal_routes_once() {
+        let mut network = Network::new();
+        network.add_edge("a", "0.1", "b");
+        network.add_edge("b", "0.7", "d");
+        network.add_edge("a", "0.7", "c");
+        network.add_edge("c", "0.1", "d");
+        let (a, d) = (network.map["a"], network.map["d"]);
+
+        let paths = k_cheapest(&network.graph, a, d, 5, weight);
+        assert_eq!(paths.len(), 2);
+        assert!(paths[0].edges != paths[1].edges);
+        for path in &paths {
+            assert!((path.cost - 0.8).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn k_cheapest_orders_a_diamond() {
+        let mut network = Network::new();
+        network.add_edge("a", "1", "b");
+        network.add_edge("a", "1", "c");
+        network.add_edge("b", "1", "d");
+        network.add_edge("c", "1", "d");
+        network.add_edge("b", "1", "c");
+        let (a, d) = (network.map["a"], network.map["d"]);
+
+        let paths = k_cheapest(&network.graph, a, d, 10, weight);
+        let edges = paths.iter()
+            .map(|p| p.edges.iter().map(|e| e.index()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(edges, vec![vec![0, 2], vec![1, 3], vec![0, 4, 3], vec![1, 4, 2]]);
+        let costs = paths.iter().map(|p| p.cost).collect::<Vec<_>>();
+        assert_eq!(costs, vec![2., 2., 3., 3.]);
+    }
+}
@@ -0,0 +1,169 @@
+//! Summary of a network's size and shape, to know what a layout is up against
+
+use rand::{seq, Rng};
+use petgraph::Graph;
+use graph::{Node, Edge};
+use geometry::MultiEdges;
+use super::{neighbours, distances, components};
+use super::cores::core_numbers;
+
+#[derive(Clone, Debug)]
+pub struct Statistics {
+    pub nodes: usize,
+    pub edges: usize,
+    pub self_loops: usize,
+    /// Pairs of distinct nodes joined by more than one edge
+    pub parallel_pairs: usize,
+    /// How many nodes have each number of distinct neighbours, indexed by that number
+    pub degrees: Vec<usize>,
+    /// Sizes of the connected components, largest first
+    pub components: Vec<usize>,
+    /// Fraction of all possible node pairs which interact
+    pub density: f32,
+    /// Largest k with a k-core
+    pub degeneracy: usize,
+    /// Nodes in that k-core
+    pub innermost: usize,
+    /// Longest shortest path found, a lower bound when sampled
+    pub diameter: usize,
+    /// Mean hops between connected pairs of distinct nodes
+    pub average_path_length: f32,
+    /// Nodes path lengths were measured from, all of them unless sampled
+    pub sources: usize,
+    /// Edges of each interaction type, in order of first appearance
+    pub interactions: Vec<(String, usize)>,
+}
+
+impl Statistics {
+    /// Measures path lengths from at most `samples` nodes, chosen with `rng`
+    pub fn of<R: Rng>(graph: &Graph<Node, Edge>, samples: usize, rng: &mut R) -> Statistics {
+        let n = graph.node_count();
+        let adjacency = neighbours(graph);
+        let multi = MultiEdges::of(graph);
+
+        let mut degrees = Vec::new();
+        for list in &adjacency {
+            if degrees.len() <= list.len() {
+                degrees.resize(list.len() + 1, 0);
+            }
+            degrees[list.len()] += 1;
+        }
+
+        let component = components(&adjacency);
+        let mut sizes = vec![0; component.iter().map(|&c| c + 1).max().unwrap_or(0)];
+        for &c in &component {
+            sizes[c] += 1;
+        }
+        sizes.sort_by(|a, b| b.cmp(a));
+
+        let cores = core_numbers(graph);
+        let degeneracy = cores.iter().cloned().max().unwrap_or(0);
+
+        let pairs = adjacency.iter().map(|list| list.len()).sum::<usize>() / 2;
+        let density = if n > 1 { 2. * pairs as f32 / (n as f32 * (n - 1) as f32) } else { 0. };
+
+        let sources = if n > samples {
+            seq::sample_indices(rng, n, samples)
+        } else {
+            (0..n).collect()
+        };
+        let (mut diameter, mut total, mut paths) = (0, 0, 0);
+        for &source in &sources {
+            for d in distances(&adjacency, source).into_iter().filter_map(|d| d) {
+                diameter = diameter.max(d);
+                total += d;
+                if d > 0 {
+                    paths += 1;
+                }
+            }
+        }
+
+        let mut interactions: Vec<(String, usize)> = Vec::new();
+        for edge in graph.raw_edges() {
+            match interactions.iter().position(|i| i.0 == edge.weight.interaction) {
+                Some(i) => interactions[i].1 += 1,
+                None => interactions.push((edge.weight.interaction.clone(), 1)),
+            }
+        }
+
+        Statistics {
+            nodes: n,
+            edges: graph.edge_count(),
+            self_loops: multi.self_loops,
+            parallel_pairs: multi.parallel_pairs,
+            degrees: degrees,
+            components: sizes,
+            density: density,
+            degeneracy: degeneracy,
+            innermost: cores.iter().filter(|&&k| k == degeneracy).count(),
+            diameter: diameter,
+            average_path_length: if paths > 0 { total as f32 / paths as f32 } else { 0. },
+            sources: sources.len(),
+            interactions: interactions,
+        }
+    }
+
+    pub fn is_sampled(&self) -> bool {
+        self.sources < self.nodes
+    }
+
+    pub fn isolated(&self) -> usize {
+        self.degrees.first().cloned().unwrap_or(0)
+    }
+
+    pub fn mean_degree(&self) -> f32 {
+        let total: usize = self.degrees.iter().enumerate().map(|(d, &count)| d * count).sum();
+        if self.nodes > 0 { total as f32 / self.nodes as f32 } else { 0. }
+    }
+
+    /// Degree distribution as (lowest, highest, nodes) ranges. Degrees are
+    /// listed one by one up to 8, then in doubling ranges.
+    pub fn degree_ranges(&self) -> Vec<(usize, usize, usize)> {
+        let mut ranges = Vec::new();
+        let mut low = 0;
+        while low < self.degrees.len() {
+            let high = if low < 8 { low } else { (low * 2 - 1).min(self.degrees.len() - 1) };
+            let count = self.degrees[low..high + 1].iter().sum();
+            if count > 0 {
+                ranges.push((low, high, count));
+            }
+            low = high + 1;
+        }
+        ranges
+    }
+
+    /// The statistics as text, one line each
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("nodes: {}", self.nodes),
+            format!("edges: {}", self.edges),
+            format!("isolated nodes: {}", self.isolated()),
+            format!("self-loops: {}", self.self_loops),
+            format!("node pairs with parallel edges: {}", self.parallel_pairs),
+            format!("density: {:.4}", self.density),
+            format!("mean degree: {:.2}", self.mean_degree()),
+            format!("innermost core: {}-core, {} nodes", self.degeneracy, self.innermost),
+        ];
+
+        let mut sizes = self.components.iter().take(10).map(|s| s.to_string()).collect::<Vec<_>>();
+        if self.components.len() > 10 {
+            sizes.push("...".to_string());
+        }
+        lines.push(format!("components: {} (sizes {})", self.components.len(), sizes.join(", ")));
+
+        let sampled = if self.is_sampled() { format!(", from {} sampled nodes", self.sources) } else { String::new() };
+        lines.push(format!("diameter: {}{}", self.diameter, sampled));
+        lines.push(format!("average path length: {:.2}{}", self.average_path_length, sampled));
+
+        lines.push("degrees:".to_string());
+        for (low, high, count) in self.degree_ranges() {
+            let degree = if low == high { low.to_string() } else { format!("{}-{}", low, high) };
+            lines.push(format!("  {}: {}", degree, count));
+        }
+        lines.push("interactions:".to_string());
+        for &(ref interaction, count) in &self.interactions {
+            lines.push(format!("  {}: {}", interaction, count));
+        }
+        lines
+    }
+}
@@ -0,0 +1,476 @@
+//! Command-line arguments: a subcommand, its input files and options
+
+use std::path::Path;
+use life::analysis::{self, Centrality};
+use life::analysis::community::Method;
+use life::analysis::cores::Filter;
+use life::export::pdf::Page;
+use life::graph::search::Pattern;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    View,
+    Layout,
+    Render,
+    Record,
+    Convert,
+    Stats,
+    Batch,
+    Defaults,
+    Path,
+    Extract,
+}
+
+impl Command {
+    fn parse(name: &str) -> Option<Command> {
+        match name {
+            "view" => Some(Command::View),
+            "layout" => Some(Command::Layout),
+            "render" => Some(Command::Render),
+            "record" => Some(Command::Record),
+            "convert" => Some(Command::Convert),
+            "stats" => Some(Command::Stats),
+            "batch" => Some(Command::Batch),
+            "defaults" => Some(Command::Defaults),
+            "path" => Some(Command::Path),
+            "extract" => Some(Command::Extract),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Command::View => "view",
+            Command::Layout => "layout",
+            Command::Render => "render",
+            Command::Record => "record",
+            Command::Convert => "convert",
+            Command::Stats => "stats",
+            Command::Batch => "batch",
+            Command::Defaults => "defaults",
+            Command::Path => "path",
+            Command::Extract => "extract",
+        }
+    }
+
+    /// Options taking a value which the command accepts
+    fn options(&self) -> &'static [&'static str] {
+        match *self {
+            Command::View => &["--config", "--algo", "--iterations", "--seed", "--size", "--page", "--margin", "--fps", "--centrality",
+                "--communities", "--resolution", "--community-attraction", "--from", "--to", "--k", "--weights", "--core", "--min-degree", "--hide", "--ego", "--hops", "--find"],
+            Command::Layout => &["--config", "-o", "--algo", "--iterations", "--seed", "--size",
+                "--communities", "--resolution", "--community-attraction"],
+            Command::Render => &["--config", "-o", "--algo", "--iterations", "--seed", "--size", "--page", "--margin", "--scale", "--centrality",
+                "--communities", "--resolution", "--community-attraction", "--from", "--to", "--k", "--weights", "--core", "--min-degree", "--hide", "--ego", "--hops", "--find"],
+            Command::Record => &["--config", "-o", "--algo", "--iterations", "--seed", "--size", "--scale", "--fps", "--centrality",
+                "--communities", "--resolution", "--community-attraction", "--from", "--to", "--k", "--weights", "--core", "--min-degree", "--hide", "--ego", "--hops", "--find"],
+            Command::Convert => &["--config", "-o"],
+            Command::Stats => &["--samples", "--seed", "--centrality", "--communities", "--resolution"],
+            Command::Batch => &["--config", "-d", "--formats", "--algo", "--iterations", "--seed", "--size", "--page", "--margin", "--scale", "--centrality",
+                "--communities", "--resolution", "--community-attraction", "--core", "--min-degree", "--hide"],
+            Command::Defaults => &[],
+            Command::Path => &["--from", "--to", "--k", "--weights"],
+            Command::Extract => &["--config", "-o", "--algo", "--iterations", "--seed", "--size",
+                "--communities", "--resolution", "--community-attraction", "--nodes", "--find", "--community",
+                "--core", "--min-degree", "--hide", "--ego", "--hops"],
+        }
+    }
+
+    /// Extensions the command can write, if it writes anything
+    fn outputs(&self) -> &'static [&'static str] {
+        match *self {
+            Command::View | Command::Stats | Command::Defaults | Command::Path => &[],
+            Command::Layout | Command::Convert | Command::Extract => &GRAPH_FORMATS,
+            Command::Render => &PICTURE_FORMATS,
+            // checked against `--formats` rather than an output file
+            Command::Batch => &[],
+            Command::Record => &["gif", "png"],
+        }
+    }
+}
+
+const GRAPH_FORMATS: [&'static str; 8] = ["sif", "cyjs", "json", "graphml", "gexf", "dot", "gv", "tex"];
+const PICTURE_FORMATS: [&'static str; 3] = ["svg", "pdf", "png"];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Algorithm {
+    FruchtermanReingold,
+}
+
+pub struct Options {
+    /// None when asking for general help
+    pub command: Option<Command>,
+    pub help: bool,
+    /// Input files, `-` for stdin
+    pub inputs: Vec<String>,
+    pub output: Option<String>,
+    /// JSON settings file, which the other options override
+    pub config: Option<String>,
+    /// Where `batch` writes
+    pub dir: Option<String>,
+    /// Extensions `batch` writes each input as
+    pub formats: Vec<String>,
+    pub algorithm: Algorithm,
+    /// Layout iterations, if not the algorithm's default
+    pub iterations: Option<usize>,
+    pub seed: Option<usize>,
+    /// Most nodes path lengths are measured from, by `stats` and betweenness
+    pub samples: usize,
+    /// Measure shown by node size and colour, or listed by `stats`
+    pub centrality: Option<Centrality>,
+    /// How to find communities, which colour the nodes, if at all
+    pub communities: Option<Method>,
+    pub resolution: f32,
+    /// Pull towards each community's centre during layout, overriding the configuration
+    pub community_attraction: Option<f32>,
+    /// Ends of the paths to find, and highlight when drawing
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// How many of the shortest paths
+    pub k: usize,
+    /// Nodes to draw, by k-core and degree
+    pub filter: Filter,
+    /// Nodes to pick out by name
+    pub find: Option<Pattern>,
+    /// Nodes `extract` keeps, by name and by community
+    pub nodes: Vec<String>,
+    pub community: Option<usize>,
+    /// Interaction types not drawn
+    pub hide: Vec<String>,
+    /// Nodes whose neighbourhood alone is drawn, and how many hops out, 1 if not given
+    pub ego: Vec<String>,
+    pub hops: Option<usize>,
+    /// Cost of each interaction type, 1 if not listed. Paths count hops without them.
+    pub weights: Option<Vec<(String, f32)>>,
+    /// Size of the layout area and of pictures, in pixels, overriding the configuration
+    pub size: Option<(f32, f32)>,
+    pub page: Page,
+    /// Resolution of raster images, as a multiple of `size`
+    pub scale: f32,
+    pub fps: f32,
+}
+
+pub const USAGE: &'static str = "\
+usage: life <command> [options] FILE[.gz]...
+
+Reads SIF, GraphML, DOT, GML, CSV or Cytoscape JSON, `-` for stdin.
+Several files are merged, and their edges coloured by file.
+
+commands:
+  view FILE...             open the network in a window (the default command)
+  layout FILE... -o OUT    lay out and save positions as .cyjs/.json, .graphml, .gexf, .dot, .tex or .sif
+  render FILE... -o OUT    lay out and draw as .svg, .pdf or .png
+  record FILE... -o OUT    draw every layout iteration into a .gif, or numbered .png frames
+  convert IN OUT           change format without laying out
+  stats FILE...            print counts, degrees, components and path lengths
+  batch FILE... -d DIR     lay out each file separately, writing DIR/NAME.cyjs and other formats
+  path FILE... --from A --to B
+                           print the shortest paths between two nodes
+  extract FILE... -o OUT   save part of the network, picked by name, community, k-core or neighbourhood
+  defaults                 print the default settings, to start a --config file from
+
+Run `life <command> --help` for its options.
+Exit status is 0 on success, 1 if reading or writing failed and 2 for invalid arguments.";
+
+const LAYOUT_OPTIONS: &'static str = "
+  --config FILE      JSON settings for the layout, colours, window and keys, see `life defaults`
+  --algo NAME        layout algorithm, only `fr` (Fruchterman-Reingold) so far
+  --iterations N     layout iterations
+  --seed N           seed for the random starting positions, for repeatable layouts
+  --size WxH         layout area and picture size in pixels
+  --communities NAME colour nodes by community, found with louvain or leiden
+  --resolution R     higher finds more, smaller communities, default 1
+  --community-attraction F
+                     pull nodes towards their community's centre, relative to an edge, default 0";
+
+const DRAWING_OPTIONS: &'static str = "
+  --centrality NAME  size and colour nodes by degree, betweenness, closeness, eigenvector or pagerank
+  --core K           draw only the K-core, where every node has at least K neighbours
+  --min-degree D     draw only nodes with at least D neighbours
+  --hide LIST        interaction types not to draw, eg. pd,OVERLAY, along with nodes left without edges";
+
+const NODE_OPTIONS: &'static str = "
+  --from A --to B    highlight the shortest path between two nodes
+  --k N              highlight the N shortest paths
+  --weights LIST     interaction costs for paths, eg. pp=1,pd=2, otherwise paths count hops
+  --find QUERY       highlight nodes named QUERY, starting with it if it ends in *, or matching /REGEX/
+  --ego A,B,...      draw only the neighbourhood of these nodes
+  --hops N           how far the neighbourhood reaches, default 1";
+
+pub fn help(command: Option<Command>) -> String {
+    let (usage, options) = match command {
+        None => return USAGE.to_string(),
+        Some(Command::View) => ("life view FILE... | life view -",
+            "  --page SIZE        paper for PDF exports, see render\n  --margin PT        PDF margin in points\n  --fps N            frame rate of recordings\n\n\
+             A single `-` follows SIF on stdin as it arrives.\n\
+             keys: S/G/M/D/T save .layout.cyjs/.gexf/.graphml/.dot/.tex beside the input, O the part shown as .part.cyjs,\n\
+             E/P/I export .svg/.pdf/.png, R records a .gif, Tab shows statistics,\n\
+             C cycles through centralities, L shows communities, Up/Down step the k-core and\n\
+             Left/Right the least degree shown, also set by the sliders at the top right, X/Z expand/collapse the neighbourhood of the node under the pointer,\n\
+             K lists interaction types, toggled by clicking them or with 1-9,\n\
+             F searches like --find, N/B centre on the next/previous node found,\n\
+             rebound under `keys` in the --config file"),
+        Some(Command::Layout) => ("life layout FILE... -o OUT", ""),
+        Some(Command::Render) => ("life render FILE... -o OUT.svg|OUT.pdf|OUT.png",
+            "  --page SIZE        PDF paper: a3, a4, a5, letter, legal or WxH in points, default a4\n  --margin PT        PDF margin in points, default 36\n  --scale N          PNG pixels per drawing pixel, default 1"),
+        Some(Command::Record) => ("life record FILE... -o OUT.gif|OUT.png",
+            "  --fps N            frames per second, default 10\n  --scale N          pixels per drawing pixel, default 1\n\n\
+             PNG frames are numbered after OUT, eg. out-0000.png"),
+        Some(Command::Convert) => ("life convert IN OUT", "  --config FILE      JSON settings, for the colours of formats which keep them\n\n\
+             OUT is .cyjs/.json, .graphml, .gexf, .dot, .tex or .sif"),
+        Some(Command::Defaults) => ("life defaults > life.json", ""),
+        Some(Command::Path) => ("life path FILE... --from A --to B",
+            "  --k N              list the N shortest paths, default 1\n  \
+             --weights LIST     cost of each interaction, eg. pp=1,pd=2, unlisted ones costing 1\n\n\
+             Without weights the path with fewest hops is found breadth first, with them by Dijkstra's\n\
+             algorithm, and several paths by Yen's. Directions are ignored."),
+        Some(Command::Extract) => ("life extract FILE... -o OUT [--nodes A,B,...] [--find QUERY] [--community N] ...",
+            "  --nodes A,B,...    keep these nodes\n  \
+             --find QUERY       keep nodes named QUERY, starting with it if it ends in *, or matching /REGEX/\n  \
+             --community N      keep the Nth largest community found with --communities, counting from 0\n  \
+             --core K           keep the K-core, where every node has at least K neighbours\n  \
+             --min-degree D     keep nodes with at least D neighbours\n  \
+             --ego A,B,...      keep the neighbourhood of these nodes\n  \
+             --hops N           how far the neighbourhood reaches, default 1\n  \
+             --hide LIST        leave out these interaction types, and nodes left without edges\n\n\
+             Nodes picked by every option given are kept, with the edges between them. Positions are\n\
+             laid out unless the input has them, and kept by .cyjs/.json, .graphml, .gexf, .dot and .tex."),
+        Some(Command::Stats) => ("life stats FILE...",
+            "  --samples N        most nodes to measure path lengths from, default 200\n  \
+             --seed N           seed for choosing them, for repeatable figures\n  \
+             --centrality NAME  also list the ten most central nodes: degree, betweenness, closeness, eigenvector or pagerank\n  \
+             --communities NAME also find communities, with louvain or leiden\n  \
+             --resolution R     higher finds more, smaller communities, default 1\n\n\
+             Larger networks are sampled, making the diameter a lower bound."),
+        Some(Command::Batch) => ("life batch FILE... -d DIR [--formats cyjs,graphml,svg,...]",
+            "  --formats LIST     comma separated extensions to write, graph or picture, default cyjs\n  \
+             --page SIZE        PDF paper, see render\n  --margin PT        PDF margin in points\n  --scale N          PNG pixels per drawing pixel\n\n\
+             Never opens a window. Inputs that fail are reported and skipped, and the exit status is then 1."),
+    };
+    let layout = match command {
+        Some(Command::Convert) | Some(Command::Stats) | Some(Command::Defaults) | Some(Command::Path) => "",
+        _ => LAYOUT_OPTIONS,
+    };
+    let drawing = match command {
+        Some(Command::View) | Some(Command::Render) | Some(Command::Record) | Some(Command::Batch) => DRAWING_OPTIONS,
+        _ => "",
+    };
+    let nodes = match command {
+        Some(Command::View) | Some(Command::Render) | Some(Command::Record) => NODE_OPTIONS,
+        _ => "",
+    };
+    format!("usage: {}\n{}{}{}\n{}", usage, layout, drawing, nodes, options).trim_end().to_string()
+}
+
+fn number<T: ::std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value '{}' for {}", value, option))
+}
+
+fn positive(option: &str, value: &str) -> Result<f32, String> {
+    match number::<f32>(option, value)? {
+        v if v > 0. => Ok(v),
+        _ => Err(format!("{} must be positive", option)),
+    }
+}
+
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut args = args.into_iter().peekable();
+    let mut options = Options {
+        command: None,
+        help: false,
+        inputs: Vec::new(),
+        output: None,
+        config: None,
+        dir: None,
+        formats: vec!["cyjs".to_string()],
+        algorithm: Algorithm::FruchtermanReingold,
+        iterations: None,
+        seed: None,
+        samples: analysis::SAMPLES,
+        centrality: None,
+        communities: None,
+        resolution: 1.,
+        community_attraction: None,
+        from: None,
+        to: None,
+        k: 1,
+        filter: Filter::default(),
+        find: None,
+        nodes: Vec::new(),
+        community: None,
+        hide: Vec::new(),
+        ego: Vec::new(),
+        hops: None,
+        weights: None,
+        size: None,
+        page: Page::a4(),
+        scale: 1.,
+        fps: 10.,
+    };
+
+    // without a known command, the arguments are files to view
+    let first = args.peek().cloned();
+    match first.as_ref().map(|s| s.as_str()) {
+        None => return Err("no command given".to_string()),
+        Some("-h") | Some("--help") | Some("help") => {
+            args.next();
+            options.help = true;
+            options.command = args.next().and_then(|name| Command::parse(&name));
+            return Ok(options);
+        },
+        Some(name) => match Command::parse(name) {
+            Some(command) => {
+                args.next();
+                options.command = Some(command);
+            },
+            None if name == "-" || !name.starts_with('-') => options.command = Some(Command::View),
+            None => return Err(format!("unknown option {}", name)),
+        },
+    }
+    let command = options.command.unwrap();
+
+    let mut margin = None;
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            options.help = true;
+            return Ok(options);
+        }
+        if arg == "-" || !arg.starts_with('-') {
+            options.inputs.push(arg);
+            continue;
+        }
+        let option = match arg.as_str() {
+            "--output" => "-o".to_string(),
+            "--dir" => "-d".to_string(),
+            _ => arg,
+        };
+        if !command.options().contains(&option.as_str()) {
+            return Err(format!("{} is not an option of `{}`", option, command.name()));
+        }
+        let value = match args.next() {
+            Some(value) => value,
+            None => return Err(format!("{} needs a value", option)),
+        };
+        match option.as_str() {
+            "-o" => options.output = Some(value),
+            "--config" => options.config = Some(value),
+            "-d" => options.dir = Some(value),
+            "--formats" => {
+                options.formats = value.split(',').map(|f| f.trim().trim_start_matches('.').to_lowercase()).collect();
+                for format in &options.formats {
+                    if !GRAPH_FORMATS.contains(&format.as_str()) && !PICTURE_FORMATS.contains(&format.as_str()) {
+                        return Err(format!("unknown format '{}'", format));
+                    }
+                }
+            },
+            "--algo" => options.algorithm = match value.to_lowercase().as_str() {
+                "fr" | "fruchterman-reingold" => Algorithm::FruchtermanReingold,
+                _ => return Err(format!("unknown layout algorithm '{}'", value)),
+            },
+            "--iterations" => options.iterations = Some(number(&option, &value)?),
+            "--seed" => options.seed = Some(number(&option, &value)?),
+            "--samples" => options.samples = match number(&option, &value)? {
+                0 => return Err(format!("{} must be positive", option)),
+                n => n,
+            },
+            "--size" => {
+                let mut parts = value.split('x');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(w), Some(h), None) => {
+                        options.size = Some((positive(&option, w)?, positive(&option, h)?));
+                    },
+                    _ => return Err(format!("invalid size '{}', expected WIDTHxHEIGHT", value)),
+                }
+            },
+            "--centrality" => options.centrality = Some(Centrality::parse(&value).ok_or(format!("unknown centrality '{}'", value))?),
+            "--communities" => options.communities = Some(Method::parse(&value).ok_or(format!("unknown community method '{}'", value))?),
+            "--resolution" => options.resolution = positive(&option, &value)?,
+            "--community-attraction" => options.community_attraction = match number::<f32>(&option, &value)? {
+                v if v >= 0. => Some(v),
+                _ => return Err(format!("{} can't be negative", option)),
+            },
+            "--from" => options.from = Some(value),
+            "--to" => options.to = Some(value),
+            "--k" => options.k = match number(&option, &value)? {
+                0 => return Err(format!("{} must be positive", option)),
+                k => k,
+            },
+            "--weights" => {
+                let mut weights = Vec::new();
+                for pair in value.split(',') {
+                    let mut parts = pair.splitn(2, '=');
+                    match (parts.next(), parts.next()) {
+                        (Some(interaction), Some(weight)) => weights.push((interaction.trim().to_string(), positive(&option, weight.trim())?)),
+                        _ => return Err(format!("invalid weight '{}', expected INTERACTION=WEIGHT", pair)),
+                    }
+                }
+                options.weights = Some(weights);
+            },
+            "--core" => options.filter.core = number(&option, &value)?,
+            "--min-degree" => options.filter.degree = number(&option, &value)?,
+            "--nodes" => options.nodes = value.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect(),
+            "--community" => options.community = Some(number(&option, &value)?),
+            "--find" => options.find = Some(Pattern::parse(&value)?),
+            "--hide" => options.hide = value.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect(),
+            "--ego" => options.ego = value.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect(),
+            "--hops" => options.hops = Some(number(&option, &value)?),
+            "--page" => options.page = Page::parse(&value).ok_or(format!("unknown page size '{}'", value))?,
+            "--margin" => margin = Some(number::<f32>(&option, &value)?),
+            "--scale" => options.scale = positive(&option, &value)?,
+            "--fps" => options.fps = positive(&option, &value)?,
+            _ => unreachable!(),
+        }
+    }
+    if let Some(margin) = margin {
+        options.page.margin = margin;
+    }
+
+    // `convert IN OUT`
+    if command == Command::Convert && options.output.is_none() && options.inputs.len() == 2 {
+        options.output = options.inputs.pop();
+    }
+
+    if command == Command::Defaults {
+        if !options.inputs.is_empty() {
+            return Err("`defaults` takes no files".to_string());
+        }
+        return Ok(options);
+    }
+    if options.inputs.is_empty() {
+        return Err(format!("`{}` needs an input file", command.name()));
+    }
+    if command == Command::Convert && options.inputs.len() > 1 {
+        return Err("`convert` takes one input file".to_string());
+    }
+    if options.from.is_some() != options.to.is_some() {
+        return Err("--from and --to go together".to_string());
+    }
+    if options.hops.is_some() && options.ego.is_empty() {
+        return Err("--hops needs --ego".to_string());
+    }
+    if options.community.is_some() && options.communities.is_none() {
+        return Err("--community needs --communities".to_string());
+    }
+    if command == Command::Extract && options.nodes.is_empty() && options.find.is_none() && options.community.is_none()
+        && !options.filter.is_active() && options.hide.is_empty() && options.ego.is_empty() {
+        return Err("`extract` needs nodes to keep, eg. --nodes, --find, --community or --core".to_string());
+    }
+    if command == Command::Path && options.from.is_none() {
+        return Err("`path` needs --from and --to".to_string());
+    }
+    if command == Command::Batch && options.dir.is_none() {
+        return Err("`batch` needs an output directory, given with -d".to_string());
+    }
+    if !command.outputs().is_empty() {
+        let output = match options.output {
+            Some(ref output) => output,
+            None => return Err(format!("`{}` needs an output file, given with -o", command.name())),
+        };
+        let extension = Path::new(output).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        if !command.outputs().contains(&extension.as_str()) {
+            return Err(format!("`{}` can't write '{}', use one of .{}", command.name(), output, command.outputs().join(", .")));
+        }
+    }
+
+    Ok(options)
+}
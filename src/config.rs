@@ -0,0 +1,150 @@
+//! Settings for the layout, colours, window and keys, read from a JSON file.
+//!
+//! Every field may be left out of the file, taking its default instead.
+
+use std::io::Read;
+use std::fs::File;
+use std::path::Path;
+use serde_json;
+use io::LoadError;
+use layout::ForceDirected;
+use style::Style;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub layout: Layout,
+    pub style: Style,
+    pub window: Window,
+    pub keys: Keys,
+}
+
+/// Parameters of `layout::ForceDirected`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    pub width: f32,
+    pub height: f32,
+    pub iterations: usize,
+    pub epsilon: f32,
+    pub temperature: f32,
+    pub community_attraction: f32,
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        let layout = ForceDirected::new(800., 600.);
+        Layout {
+            width: layout.width,
+            height: layout.height,
+            iterations: layout.iterations,
+            epsilon: layout.epsilon,
+            temperature: layout.temperature,
+            community_attraction: layout.community_attraction,
+        }
+    }
+}
+
+impl Layout {
+    pub fn force_directed(&self) -> ForceDirected {
+        let mut layout = ForceDirected::new(self.width, self.height);
+        layout.iterations = self.iterations;
+        layout.epsilon = self.epsilon;
+        layout.temperature = self.temperature;
+        layout.community_attraction = self.community_attraction;
+        layout
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Window {
+    pub width: u32,
+    pub height: u32,
+    pub title: String,
+}
+
+impl Default for Window {
+    fn default() -> Window {
+        Window {
+            width: 800,
+            height: 600,
+            title: "life".to_string(),
+        }
+    }
+}
+
+/// Keys of the window's commands, named as on the keyboard: `S`, `F5`, `Space`...
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+    pub save_cyjs: String,
+    pub save_gexf: String,
+    pub save_graphml: String,
+    pub save_dot: String,
+    pub save_tikz: String,
+    /// Saves only the nodes and edges shown, as .part.cyjs
+    pub save_shown: String,
+    pub export_svg: String,
+    pub export_pdf: String,
+    pub export_png: String,
+    pub record: String,
+    pub statistics: String,
+    pub centrality: String,
+    pub communities: String,
+    pub core_up: String,
+    pub core_down: String,
+    pub degree_up: String,
+    pub degree_down: String,
+    pub expand: String,
+    pub collapse: String,
+    pub interactions: String,
+    pub find: String,
+    pub next_found: String,
+    pub previous_found: String,
+    /// Each toggles the interaction type listed in that place
+    pub interaction_types: Vec<String>,
+}
+
+impl Default for Keys {
+    fn default() -> Keys {
+        Keys {
+            save_cyjs: "S".to_string(),
+            save_gexf: "G".to_string(),
+            save_graphml: "M".to_string(),
+            save_dot: "D".to_string(),
+            save_tikz: "T".to_string(),
+            save_shown: "O".to_string(),
+            export_svg: "E".to_string(),
+            export_pdf: "P".to_string(),
+            export_png: "I".to_string(),
+            record: "R".to_string(),
+            statistics: "Tab".to_string(),
+            centrality: "C".to_string(),
+            communities: "L".to_string(),
+            core_up: "Up".to_string(),
+            core_down: "Down".to_string(),
+            degree_up: "Right".to_string(),
+            degree_down: "Left".to_string(),
+            expand: "X".to_string(),
+            collapse: "Z".to_string(),
+            interactions: "K".to_string(),
+            find: "F".to_string(),
+            next_found: "N".to_string(),
+            previous_found: "B".to_string(),
+            interaction_types: (1..10).map(|i| i.to_string()).collect(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, LoadError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
@@ -12,9 +12,12 @@ pub enum Status {
 impl Core {
 
     pub fn initialize() -> Core {
-        Core {
-            window: Window::new(),
+        Core::with_window(Window::new())
+    }
 
+    pub fn with_window(window: Window) -> Core {
+        Core {
+            window: window,
         }
     }
 }
@@ -8,11 +8,15 @@ pub struct Window {
 
 impl Window {
     pub fn new() -> Window {
+        Window::with_size(800, 600, "life")
+    }
+
+    pub fn with_size(width: u32, height: u32, title: &str) -> Window {
         let events_loop = glium::glutin::EventsLoop::new();
 
         let window = glium::glutin::WindowBuilder::new()
-            .with_dimensions(800, 600)
-            .with_title("life");
+            .with_dimensions(width, height)
+            .with_title(title);
 
         let context = glium::glutin::ContextBuilder::new()
             .with_vsync(true)
@@ -0,0 +1,341 @@
+//! Description of a picture of the network, independent of how it ends up on screen or paper.
+//!
+//! Everything is in view coordinates: pixels from the top left corner, y pointing down.
+
+use std::collections::HashSet;
+use cgmath::{Vector2, InnerSpace};
+use petgraph::Graph;
+use petgraph::graph::{NodeIndex, EdgeIndex};
+use graph::{Node, Edge};
+use geometry;
+use style::{self, Colour, Style};
+
+/// Half the width of a node square on screen, as drawn by the window
+pub const NODE_SIZE: f32 = 2.0;
+/// Largest node when sizes show a measure, as a multiple of `NODE_SIZE`
+pub const MAX_NODE_SCALE: f32 = 4.0;
+/// Radius of a self-loop on screen
+pub const LOOP_RADIUS: f32 = 8.0;
+pub const FONT_SIZE: f32 = 10.0;
+
+/// The part of the layout in view, matching the window's projection
+#[derive(Copy, Clone, Debug)]
+pub struct Camera {
+    pub width: f32,
+    pub height: f32,
+    /// Pixels per layout unit
+    pub zoom: f32,
+    /// Offset of the layout origin from the centre of the view, in pixels
+    pub translation: Vector2<f32>,
+}
+
+impl Camera {
+    /// A camera showing every node, with `margin` pixels to spare on each side
+    pub fn fit(graph: &Graph<Node, Edge>, width: f32, height: f32, margin: f32) -> Camera {
+        Camera::around(graph.node_indices().map(|v| graph[v].pos), width, height, margin)
+    }
+
+    /// A camera showing all the given layout positions, eg. every frame of an animation
+    pub fn around<I: IntoIterator<Item = Vector2<f32>>>(points: I, width: f32, height: f32, margin: f32) -> Camera {
+        let mut points = points.into_iter();
+        let first = match points.next() {
+            Some(pos) => pos,
+            None => return Camera { width: width, height: height, zoom: 1., translation: Vector2::new(0., 0.) },
+        };
+        let (min, max) = points.fold((first, first), |(min, max), p| {
+            (Vector2::new(min.x.min(p.x), min.y.min(p.y)), Vector2::new(max.x.max(p.x), max.y.max(p.y)))
+        });
+
+        let size = max - min;
+        let fit_x = if size.x > 0. { (width - 2. * margin).max(1.) / size.x } else { 1. };
+        let fit_y = if size.y > 0. { (height - 2. * margin).max(1.) / size.y } else { 1. };
+        let zoom = fit_x.min(fit_y);
+        let centre = (min + max) / 2.;
+
+        Camera {
+            width: width,
+            height: height,
+            zoom: zoom,
+            translation: -centre * zoom,
+        }
+    }
+
+    pub fn to_view(&self, p: Vector2<f32>) -> Vector2<f32> {
+        Vector2::new(self.width / 2. + p.x * self.zoom + self.translation.x,
+                     self.height / 2. - (p.y * self.zoom + self.translation.y))
+    }
+
+    /// The node drawn closest to a point in the view, if any is within `within` pixels
+    pub fn nearest<I: IntoIterator<Item = NodeIndex>>(&self, graph: &Graph<Node, Edge>, nodes: I, point: Vector2<f32>, within: f32) -> Option<NodeIndex> {
+        nodes.into_iter()
+            .map(|v| (v, (self.to_view(graph[v].pos) - point).magnitude()))
+            .filter(|&(_, d)| d <= within)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(v, _)| v)
+    }
+}
+
+pub struct Line {
+    pub points: Vec<Vector2<f32>>,
+    pub colour: Colour,
+}
+
+pub struct Square {
+    pub centre: Vector2<f32>,
+    /// Half the width
+    pub size: f32,
+    pub colour: Colour,
+}
+
+pub struct Label {
+    /// Left end of the baseline
+    pub position: Vector2<f32>,
+    pub text: String,
+    pub size: f32,
+    pub colour: Colour,
+}
+
+/// Size of a node showing a measure scaled from 0 to 1, as a multiple of `NODE_SIZE`
+pub fn node_scale(t: f32) -> f32 {
+    1. + (MAX_NODE_SCALE - 1.) * t.sqrt()
+}
+
+/// Nodes and edges to pick out in `Style::highlight`, dimming the rest
+#[derive(Clone, Debug, Default)]
+pub struct Highlight {
+    pub nodes: HashSet<NodeIndex>,
+    pub edges: HashSet<EdgeIndex>,
+}
+
+impl Highlight {
+    pub fn new<N, E>(nodes: N, edges: E) -> Highlight
+        where N: IntoIterator<Item = NodeIndex>, E: IntoIterator<Item = EdgeIndex> {
+        Highlight {
+            nodes: nodes.into_iter().collect(),
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// Picks out what either picks out
+    pub fn join(mut self, other: Highlight) -> Highlight {
+        self.nodes.extend(other.nodes);
+        self.edges.extend(other.edges);
+        self
+    }
+
+    pub fn node_colour(&self, v: NodeIndex, colour: Colour, style: &Style) -> Colour {
+        if self.nodes.contains(&v) { style.highlight } else { style.dim(colour) }
+    }
+
+    pub fn edge_colour(&self, e: EdgeIndex, colour: Colour, style: &Style) -> Colour {
+        if self.edges.contains(&e) { style.highlight } else { style.dim(colour) }
+    }
+}
+
+/// Which nodes and edges are drawn, by index. An edge is shown only along
+/// with both its ends.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Visible {
+    pub nodes: Vec<bool>,
+    pub edges: Vec<bool>,
+}
+
+impl Visible {
+    pub fn nodes(graph: &Graph<Node, Edge>, nodes: Vec<bool>) -> Visible {
+        let edges = graph.raw_edges().iter().map(|e| nodes[e.source().index()] && nodes[e.target().index()]).collect();
+        Visible {
+            nodes: nodes,
+            edges: edges,
+        }
+    }
+
+    pub fn node(&self, v: NodeIndex) -> bool {
+        self.nodes[v.index()]
+    }
+
+    pub fn edge(&self, e: EdgeIndex) -> bool {
+        self.edges[e.index()]
+    }
+
+    /// Shown nodes and edges
+    pub fn counts(&self) -> (usize, usize) {
+        (self.nodes.iter().filter(|&&v| v).count(), self.edges.iter().filter(|&&e| e).count())
+    }
+}
+
+/// Interaction types in order of first appearance, each with its number of
+/// edges and whether they are shown
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Interactions {
+    pub types: Vec<(String, usize, bool)>,
+}
+
+impl Interactions {
+    /// Every type in the network, all shown
+    pub fn of(graph: &Graph<Node, Edge>) -> Interactions {
+        let mut interactions = Interactions::default();
+        interactions.update(graph);
+        interactions
+    }
+
+    /// Recounts the edges, adding any new types as shown
+    pub fn update(&mut self, graph: &Graph<Node, Edge>) {
+        for t in &mut self.types {
+            t.1 = 0;
+        }
+        for edge in graph.raw_edges() {
+            match self.types.iter().position(|t| t.0 == edge.weight.interaction) {
+                Some(i) => self.types[i].1 += 1,
+                None => self.types.push((edge.weight.interaction.clone(), 1, true)),
+            }
+        }
+    }
+
+    /// Shows or hides a type by name, returning false if there is no such type
+    pub fn set(&mut self, interaction: &str, shown: bool) -> bool {
+        match self.types.iter_mut().find(|t| t.0 == interaction) {
+            Some(t) => {
+                t.2 = shown;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Shows the `i`th type if hidden or hides it if shown, returning whether it's shown now
+    pub fn toggle(&mut self, i: usize) -> Option<bool> {
+        self.types.get_mut(i).map(|t| {
+            t.2 = !t.2;
+            t.2
+        })
+    }
+
+    pub fn shows(&self, interaction: &str) -> bool {
+        self.types.iter().find(|t| t.0 == interaction).map_or(true, |t| t.2)
+    }
+
+    pub fn hides_any(&self) -> bool {
+        self.types.iter().any(|t| !t.2)
+    }
+
+    /// Restricts what is visible to edges of shown types, and hides the nodes
+    /// left without any edge. Nodes which had none to begin with stay.
+    pub fn restrict(&self, graph: &Graph<Node, Edge>, visible: &mut Visible) {
+        let mut linked = vec![false; graph.node_count()];
+        let mut kept = vec![false; graph.node_count()];
+        for (shown, edge) in visible.edges.iter_mut().zip(graph.raw_edges()) {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            linked[a] = true;
+            linked[b] = true;
+            if self.shows(&edge.weight.interaction) {
+                kept[a] = true;
+                kept[b] = true;
+            } else {
+                *shown = false;
+            }
+        }
+        // their edges were all hidden already
+        for (v, shown) in visible.nodes.iter_mut().enumerate() {
+            *shown = *shown && (kept[v] || !linked[v]);
+        }
+    }
+}
+
+/// Shapes in drawing order: lines, then squares, then labels
+pub struct Drawing {
+    pub width: f32,
+    pub height: f32,
+    pub background: Colour,
+    pub lines: Vec<Line>,
+    pub squares: Vec<Square>,
+    pub labels: Vec<Label>,
+}
+
+impl Drawing {
+    pub fn new(graph: &Graph<Node, Edge>, style: &Style, merged: bool, camera: &Camera, labels: bool) -> Drawing {
+        let multiplicities = geometry::multiplicities(graph);
+        let lines = graph.edge_indices().map(|e| {
+            let path = geometry::edge_path(graph, e, multiplicities[e.index()], LOOP_RADIUS / camera.zoom);
+            Line {
+                points: path.into_iter().map(|p| camera.to_view(p)).collect(),
+                colour: style.edge_colour(&graph[e], merged),
+            }
+        }).collect();
+
+        let squares = graph.node_indices().map(|v| {
+            Square {
+                centre: camera.to_view(graph[v].pos),
+                size: NODE_SIZE,
+                colour: match graph[v].community {
+                    Some(c) => style::categorical(c, style.node[3]),
+                    None => style.node,
+                },
+            }
+        }).collect();
+
+        let labels = if labels {
+            graph.node_indices().map(|v| {
+                Label {
+                    position: camera.to_view(graph[v].pos) + Vector2::new(NODE_SIZE * 2., FONT_SIZE / 3.),
+                    text: graph[v].name.clone(),
+                    size: FONT_SIZE,
+                    colour: style.label,
+                }
+            }).collect()
+        } else {
+            Vec::new()
+        };
+
+        Drawing {
+            width: camera.width,
+            height: camera.height,
+            background: style.background,
+            lines: lines,
+            squares: squares,
+            labels: labels,
+        }
+    }
+
+    /// Sizes and colours the nodes by a measure scaled from 0 to 1, by node index
+    pub fn size_nodes(&mut self, values: &[f32]) {
+        for (square, &t) in self.squares.iter_mut().zip(values) {
+            square.size = NODE_SIZE * node_scale(t);
+            square.colour = style::ramp(t, square.colour[3]);
+        }
+        if self.labels.len() == self.squares.len() {
+            for (label, square) in self.labels.iter_mut().zip(&self.squares) {
+                label.position.x = square.centre.x + square.size * 2.;
+            }
+        }
+    }
+
+    /// Leaves out hidden nodes, their labels and edges. Shapes are no longer
+    /// in index order afterwards, so this comes after any other changes.
+    pub fn hide(&mut self, visible: &Visible) {
+        let mut nodes = visible.nodes.iter();
+        self.squares.retain(|_| *nodes.next().unwrap());
+        if self.labels.len() == visible.nodes.len() {
+            let mut nodes = visible.nodes.iter();
+            self.labels.retain(|_| *nodes.next().unwrap());
+        }
+        let mut edges = visible.edges.iter();
+        self.lines.retain(|_| *edges.next().unwrap());
+    }
+
+    /// Recolours the highlighted nodes and edges, dimming the others
+    pub fn highlight(&mut self, highlight: &Highlight, style: &Style) {
+        for (v, square) in self.squares.iter_mut().enumerate() {
+            square.colour = highlight.node_colour(NodeIndex::new(v), square.colour, style);
+        }
+        for (e, line) in self.lines.iter_mut().enumerate() {
+            line.colour = highlight.edge_colour(EdgeIndex::new(e), line.colour, style);
+        }
+        if self.labels.len() == self.squares.len() {
+            for (v, label) in self.labels.iter_mut().enumerate() {
+                if !highlight.nodes.contains(&NodeIndex::new(v)) {
+                    label.colour = style.dim(label.colour);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Writers for pictures of the network, all working from a `draw::Drawing`
+
+pub mod svg;
+pub mod pdf;
+pub mod raster;
+pub mod png;
+pub mod record;
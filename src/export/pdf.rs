@@ -0,0 +1,199 @@
+//! Single page PDF, with the label font embedded
+
+extern crate flate2;
+
+use std::io::{self, Write};
+use std::fs::File;
+use std::path::Path;
+use self::flate2::Compression;
+use self::flate2::write::ZlibEncoder;
+use draw::Drawing;
+use font::{self, Metrics};
+
+/// Page size and margin, in points
+#[derive(Copy, Clone, Debug)]
+pub struct Page {
+    pub width: f32,
+    pub height: f32,
+    pub margin: f32,
+}
+
+impl Page {
+    pub fn a4() -> Page {
+        Page { width: 595.28, height: 841.89, margin: 36. }
+    }
+
+    pub fn letter() -> Page {
+        Page { width: 612., height: 792., margin: 36. }
+    }
+
+    /// Reads `a3`, `a4`, `a5`, `letter`, `legal` or `WIDTHxHEIGHT` in points
+    pub fn parse(name: &str) -> Option<Page> {
+        let (width, height) = match name.to_lowercase().as_str() {
+            "a3" => (841.89, 1190.55),
+            "a4" => (595.28, 841.89),
+            "a5" => (419.53, 595.28),
+            "letter" => (612., 792.),
+            "legal" => (612., 1008.),
+            other => {
+                let mut parts = other.split('x');
+                match (parts.next().and_then(|w| w.parse().ok()), parts.next().and_then(|h| h.parse().ok())) {
+                    (Some(w), Some(h)) if parts.next().is_none() => (w, h),
+                    _ => return None,
+                }
+            },
+        };
+        Some(Page { width: width, height: height, margin: 36. })
+    }
+
+    /// Turns the page sideways if that suits the drawing better
+    pub fn oriented_for(&self, drawing: &Drawing) -> Page {
+        if (drawing.width > drawing.height) != (self.width > self.height) {
+            Page { width: self.height, height: self.width, margin: self.margin }
+        } else {
+            *self
+        }
+    }
+}
+
+fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Escapes text for a PDF string, replacing anything outside printable ASCII
+fn pdf_string(text: &str) -> String {
+    let mut s = String::new();
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                s.push('\\');
+                s.push(c);
+            },
+            ' ' ..= '~' => s.push(c),
+            _ => s.push('?'),
+        }
+    }
+    s
+}
+
+/// Collects numbered objects and their offsets for the cross-reference table
+struct Document {
+    bytes: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Document {
+    fn object(&mut self, body: &str) {
+        self.offsets.push(self.bytes.len());
+        let number = self.offsets.len();
+        self.bytes.extend(format!("{} 0 obj\n{}\nendobj\n", number, body).as_bytes());
+    }
+
+    fn stream(&mut self, dictionary: &str, data: &[u8]) {
+        self.offsets.push(self.bytes.len());
+        let number = self.offsets.len();
+        self.bytes.extend(format!("{} 0 obj\n<< {}/Length {} /Filter /FlateDecode >>\nstream\n", number, dictionary, data.len()).as_bytes());
+        self.bytes.extend(data);
+        self.bytes.extend(b"\nendstream\nendobj\n");
+    }
+}
+
+fn content(drawing: &Drawing, page: &Page, alphas: &[f32]) -> String {
+    let mut c = String::new();
+    let gs = |alpha: f32| alphas.iter().position(|&a| a == alpha).unwrap();
+    let colour = |rgba: [f32; 4]| format!("{:.3} {:.3} {:.3}", rgba[0], rgba[1], rgba[2]);
+
+    // fit the drawing inside the margins, flipping y to match it
+    let (w, h) = (page.width - 2. * page.margin, page.height - 2. * page.margin);
+    let s = (w / drawing.width).min(h / drawing.height);
+    let x = page.margin + (w - s * drawing.width) / 2.;
+    let y = page.height - page.margin - (h - s * drawing.height) / 2.;
+    c.push_str(&format!("q\n{:.4} 0 0 {:.4} {:.3} {:.3} cm\n", s, -s, x, y));
+
+    c.push_str(&format!("/G{} gs {} rg 0 0 {} {} re f\n", gs(drawing.background[3]), colour(drawing.background), drawing.width, drawing.height));
+
+    c.push_str("1 J 1 j 1 w\n");
+    for line in &drawing.lines {
+        c.push_str(&format!("/G{} gs {} RG\n", gs(line.colour[3]), colour(line.colour)));
+        for (i, p) in line.points.iter().enumerate() {
+            c.push_str(&format!("{:.2} {:.2} {}\n", p.x, p.y, if i == 0 { "m" } else { "l" }));
+        }
+        c.push_str("S\n");
+    }
+
+    for square in &drawing.squares {
+        c.push_str(&format!("/G{} gs {} rg {:.2} {:.2} {:.2} {:.2} re f\n", gs(square.colour[3]), colour(square.colour),
+                            square.centre.x - square.size, square.centre.y - square.size, square.size * 2., square.size * 2.));
+    }
+
+    for label in &drawing.labels {
+        // the text matrix flips glyphs back upright
+        c.push_str(&format!("/G{} gs {} rg BT /F1 {} Tf 1 0 0 -1 {:.2} {:.2} Tm ({}) Tj ET\n", gs(label.colour[3]), colour(label.colour),
+                            label.size, label.position.x, label.position.y, pdf_string(&label.text)));
+    }
+
+    c.push_str("Q\n");
+    c
+}
+
+pub fn write<W: Write>(drawing: &Drawing, page: &Page, mut writer: W) -> io::Result<()> {
+    let page = page.oriented_for(drawing);
+
+    // one graphics state per distinct opacity
+    let mut alphas = vec![drawing.background[3]];
+    let colours = drawing.lines.iter().map(|l| l.colour)
+        .chain(drawing.squares.iter().map(|s| s.colour))
+        .chain(drawing.labels.iter().map(|l| l.colour));
+    for colour in colours {
+        if !alphas.contains(&colour[3]) {
+            alphas.push(colour[3]);
+        }
+    }
+
+    let mut document = Document {
+        bytes: b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec(),
+        offsets: Vec::new(),
+    };
+
+    let states: Vec<String> = alphas.iter().enumerate()
+        .map(|(i, a)| format!("/G{} << /CA {} /ca {} >>", i, a, a))
+        .collect();
+    let fonts = if drawing.labels.is_empty() { "" } else { "/Font << /F1 5 0 R >>" };
+
+    document.object("<< /Type /Catalog /Pages 2 0 R >>");
+    document.object("<< /Type /Pages /Kids [3 0 R] /Count 1 >>");
+    document.object(&format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << {} /ExtGState << {} >> >> /Contents 4 0 R >>",
+                             page.width, page.height, fonts, states.join(" ")));
+    document.stream("", &compress(content(drawing, &page, &alphas).as_bytes())?);
+
+    if !drawing.labels.is_empty() {
+        let bytes = font::BYTES;
+        let metrics = Metrics::parse(bytes).ok_or(io::Error::new(io::ErrorKind::InvalidData, "unreadable font"))?;
+        let advance = metrics.per_mille(metrics.advance as i16);
+        let widths: Vec<String> = (32..127).map(|_| advance.to_string()).collect();
+        let bbox: Vec<String> = metrics.bbox.iter().map(|&v| metrics.per_mille(v).to_string()).collect();
+
+        document.object(&format!("<< /Type /Font /Subtype /TrueType /BaseFont /{} /FirstChar 32 /LastChar 126 /Widths [{}] /Encoding /WinAnsiEncoding /FontDescriptor 6 0 R >>",
+                                 font::NAME, widths.join(" ")));
+        // flags: fixed pitch, non-symbolic
+        document.object(&format!("<< /Type /FontDescriptor /FontName /{} /Flags 33 /FontBBox [{}] /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 /FontFile2 7 0 R >>",
+                                 font::NAME, bbox.join(" "), metrics.per_mille(metrics.ascent), metrics.per_mille(metrics.descent), metrics.per_mille(metrics.ascent)));
+        document.stream(&format!("/Length1 {} ", bytes.len()), &compress(bytes)?);
+    }
+
+    let xref = document.bytes.len();
+    let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", document.offsets.len() + 1);
+    for offset in &document.offsets {
+        trailer.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    trailer.push_str(&format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", document.offsets.len() + 1, xref));
+    document.bytes.extend(trailer.as_bytes());
+
+    writer.write_all(&document.bytes)
+}
+
+pub fn write_file<P: AsRef<Path>>(drawing: &Drawing, page: &Page, path: P) -> io::Result<()> {
+    write(drawing, page, File::create(path)?)
+}
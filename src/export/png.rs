@@ -0,0 +1,29 @@
+//! PNG images, rasterized in software so no GPU or display is needed
+
+extern crate png;
+
+use std::io::{self, Write, BufWriter};
+use std::fs::File;
+use std::path::Path;
+use self::png::HasParameters;
+use draw::Drawing;
+use super::raster::{self, Canvas};
+
+pub fn write_canvas<W: Write>(canvas: &Canvas, writer: W) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(BufWriter::new(writer), canvas.width, canvas.height);
+    encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&canvas.to_rgba8())?;
+    Ok(())
+}
+
+/// Writes a drawing `scale` times its own size, eg. 2 for a high resolution image
+pub fn write<W: Write>(drawing: &Drawing, scale: f32, writer: W) -> io::Result<()> {
+    write_canvas(&raster::render(drawing, scale)?, writer)
+}
+
+/// Renders before creating the file, so a failure leaves no empty image behind
+pub fn write_file<P: AsRef<Path>>(drawing: &Drawing, scale: f32, path: P) -> io::Result<()> {
+    let canvas = raster::render(drawing, scale)?;
+    write_canvas(&canvas, File::create(path)?)
+}
@@ -0,0 +1,204 @@
+//! Software rasterizer for drawings, so pictures can be made without a GPU or display
+
+extern crate rusttype;
+
+use std::io;
+use cgmath::{Vector2, InnerSpace};
+use self::rusttype::{Font, Scale, point};
+use draw::Drawing;
+use font;
+use style::Colour;
+
+/// Width of edge lines, in drawing pixels, as in the other exporters
+const LINE_WIDTH: f32 = 1.;
+
+/// An RGBA image with straight alpha, blended in floating point
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<Colour>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Colour) -> Canvas {
+        Canvas {
+            width: width,
+            height: height,
+            pixels: vec![background; (width * height) as usize],
+        }
+    }
+
+    /// Paints `colour` over one pixel, covering the given fraction of it
+    fn blend(&mut self, x: i32, y: i32, colour: Colour, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let alpha = colour[3] * coverage.max(0.).min(1.);
+        if alpha <= 0. {
+            return;
+        }
+        let ref mut dst = self.pixels[(y as u32 * self.width + x as u32) as usize];
+        let out = alpha + dst[3] * (1. - alpha);
+        for i in 0..3 {
+            dst[i] = (colour[i] * alpha + dst[i] * dst[3] * (1. - alpha)) / out;
+        }
+        dst[3] = out;
+    }
+
+    /// Pixel range covering `min..max` along one axis, clipped to `size`
+    fn span(min: f32, max: f32, size: u32) -> (i32, i32) {
+        ((min.floor() as i32).max(0), (max.ceil() as i32).min(size as i32))
+    }
+
+    /// Antialiased polyline of the given width. Each pixel is painted once,
+    /// so joints don't show through translucent colours.
+    pub fn polyline(&mut self, points: &[Vector2<f32>], width: f32, colour: Colour) {
+        if points.is_empty() {
+            return;
+        }
+        let reach = width / 2. + 1.;
+        let (min, max) = points.iter().fold((points[0], points[0]), |(min, max), p| {
+            (Vector2::new(min.x.min(p.x), min.y.min(p.y)), Vector2::new(max.x.max(p.x), max.y.max(p.y)))
+        });
+        let (x0, x1) = Canvas::span(min.x - reach, max.x + reach, self.width);
+        let (y0, y1) = Canvas::span(min.y - reach, max.y + reach, self.height);
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        let stride = (x1 - x0) as usize;
+        let mut coverage = vec![0f32; stride * (y1 - y0) as usize];
+        let segments: Vec<_> = if points.len() == 1 {
+            vec![(points[0], points[0])]
+        } else {
+            points.windows(2).map(|pair| (pair[0], pair[1])).collect()
+        };
+        for (a, b) in segments {
+            let (sx0, sx1) = Canvas::span(a.x.min(b.x) - reach, a.x.max(b.x) + reach, self.width);
+            let (sy0, sy1) = Canvas::span(a.y.min(b.y) - reach, a.y.max(b.y) + reach, self.height);
+            let diff = b - a;
+            let length2 = diff.magnitude2();
+            for y in sy0..sy1 {
+                for x in sx0..sx1 {
+                    let p = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    let t = if length2 > 0. { ((p - a).dot(diff) / length2).max(0.).min(1.) } else { 0. };
+                    let distance = (p - (a + diff * t)).magnitude();
+                    let c = (width / 2. + 0.5 - distance).max(0.).min(1.);
+                    let ref mut cell = coverage[(y - y0) as usize * stride + (x - x0) as usize];
+                    *cell = cell.max(c);
+                }
+            }
+        }
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let c = coverage[(y - y0) as usize * stride + (x - x0) as usize];
+                self.blend(x, y, colour, c);
+            }
+        }
+    }
+
+    /// Axis-aligned square of half-width `size`, with partially covered edge pixels
+    pub fn fill_square(&mut self, centre: Vector2<f32>, size: f32, colour: Colour) {
+        let (left, right) = (centre.x - size, centre.x + size);
+        let (top, bottom) = (centre.y - size, centre.y + size);
+        let (x0, x1) = Canvas::span(left, right, self.width);
+        let (y0, y1) = Canvas::span(top, bottom, self.height);
+        let overlap = |from: f32, to: f32, i: i32| (to.min(i as f32 + 1.) - from.max(i as f32)).max(0.);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                self.blend(x, y, colour, overlap(left, right, x) * overlap(top, bottom, y));
+            }
+        }
+    }
+
+    pub fn fill_circle(&mut self, centre: Vector2<f32>, radius: f32, colour: Colour) {
+        let (x0, x1) = Canvas::span(centre.x - radius - 1., centre.x + radius + 1., self.width);
+        let (y0, y1) = Canvas::span(centre.y - radius - 1., centre.y + radius + 1., self.height);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let distance = (Vector2::new(x as f32 + 0.5, y as f32 + 0.5) - centre).magnitude();
+                self.blend(x, y, colour, radius + 0.5 - distance);
+            }
+        }
+    }
+
+    /// Text starting at the left end of its baseline, `size` pixels high
+    pub fn text(&mut self, font: &Font, position: Vector2<f32>, size: f32, text: &str, colour: Colour) {
+        for glyph in font.layout(text, Scale::uniform(size), point(position.x, position.y)) {
+            if let Some(bounds) = glyph.pixel_bounding_box() {
+                glyph.draw(|x, y, v| self.blend(bounds.min.x + x as i32, bounds.min.y + y as i32, colour, v));
+            }
+        }
+    }
+
+    /// The image as 8-bit RGBA rows, top to bottom
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let channel = |c: f32| (c.max(0.).min(1.) * 255.).round() as u8;
+        self.pixels.iter()
+            .flat_map(|p| vec![channel(p[0]), channel(p[1]), channel(p[2]), channel(p[3])])
+            .collect()
+    }
+}
+
+/// Rasterizes a drawing, `scale` output pixels to each drawing pixel
+pub fn render(drawing: &Drawing, scale: f32) -> io::Result<Canvas> {
+    let width = (drawing.width * scale).round().max(1.) as u32;
+    let height = (drawing.height * scale).round().max(1.) as u32;
+    let mut canvas = Canvas::new(width, height, drawing.background);
+
+    for line in &drawing.lines {
+        let points: Vec<_> = line.points.iter().map(|&p| p * scale).collect();
+        canvas.polyline(&points, LINE_WIDTH * scale, line.colour);
+    }
+
+    for square in &drawing.squares {
+        canvas.fill_square(square.centre * scale, square.size * scale, square.colour);
+    }
+
+    if !drawing.labels.is_empty() {
+        let font = Font::try_from_bytes(font::BYTES)
+            .ok_or(io::Error::new(io::ErrorKind::InvalidData, "unreadable font"))?;
+        for label in &drawing.labels {
+            canvas.text(&font, label.position * scale, label.size * scale, &label.text, label.colour);
+        }
+    }
+
+    Ok(canvas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Colour = [1., 1., 1., 1.];
+    const RED: Colour = [1., 0., 0., 1.];
+
+    fn pixel(canvas: &Canvas, x: u32, y: u32) -> Colour {
+        canvas.pixels[(y * canvas.width + x) as usize]
+    }
+
+    #[test]
+    fn fill_circle_covers_its_pixels() {
+        let mut canvas = Canvas::new(20, 20, WHITE);
+        canvas.fill_circle(Vector2::new(10., 10.), 4., RED);
+        // centres within the radius are covered, those beyond it untouched
+        for &(x, y) in &[(10, 10), (9, 9), (7, 10), (10, 12)] {
+            assert_eq!(pixel(&canvas, x, y), RED, "({}, {})", x, y);
+        }
+        for &(x, y) in &[(0, 0), (15, 10), (10, 4), (13, 13)] {
+            assert_eq!(pixel(&canvas, x, y), WHITE, "({}, {})", x, y);
+        }
+        // a pixel 4.3 from the centre is partly covered
+        let edge = pixel(&canvas, 13, 12);
+        assert!(edge[1] > 0. && edge[1] < 1., "{:?}", edge);
+        assert_eq!(edge[0], 1.);
+    }
+
+    #[test]
+    fn fill_circle_is_clipped_to_the_canvas() {
+        let mut canvas = Canvas::new(4, 4, WHITE);
+        canvas.fill_circle(Vector2::new(0., 0.), 10., RED);
+        assert!(canvas.pixels.iter().all(|&p| p == RED));
+    }
+}
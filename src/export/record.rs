@@ -0,0 +1,74 @@
+//! Recording a sequence of drawings, such as the layout converging, as an
+//! animated GIF or numbered PNG frames
+
+extern crate gif;
+
+use std::io::{self, BufWriter};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use self::gif::SetParameter;
+use draw::Drawing;
+use super::{raster, png};
+
+/// A GIF is only complete once its recording is dropped
+pub struct Recording {
+    path: PathBuf,
+    /// Hundredths of a second per frame, as GIF counts time
+    delay: u16,
+    scale: f32,
+    frames: usize,
+    /// Opened on the first frame, once the size is known
+    encoder: Option<gif::Encoder<BufWriter<File>>>,
+}
+
+impl Recording {
+    /// Records into an animated GIF if `path` ends in `.gif`, otherwise into
+    /// PNG files numbered after it, eg. `out.png` becomes `out-0000.png`, `out-0001.png`...
+    /// Frames are rasterized `scale` times the size of their drawings.
+    pub fn new<P: AsRef<Path>>(path: P, fps: f32, scale: f32) -> Recording {
+        Recording {
+            path: path.as_ref().to_path_buf(),
+            delay: (100. / fps).round().max(1.).min(u16::max_value() as f32) as u16,
+            scale: scale,
+            frames: 0,
+            encoder: None,
+        }
+    }
+
+    fn is_gif(&self) -> bool {
+        self.path.extension().map(|e| e.to_string_lossy().to_lowercase() == "gif").unwrap_or(false)
+    }
+
+    /// Where the `n`th PNG frame goes
+    fn frame_path(&self, n: usize) -> PathBuf {
+        let stem = self.path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or(String::new());
+        self.path.with_file_name(format!("{}-{:04}.png", stem, n))
+    }
+
+    pub fn frame(&mut self, drawing: &Drawing) -> io::Result<()> {
+        let canvas = raster::render(drawing, self.scale)?;
+        if self.is_gif() {
+            if canvas.width > u16::max_value() as u32 || canvas.height > u16::max_value() as u32 {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "too large for a GIF"));
+            }
+            let (width, height) = (canvas.width as u16, canvas.height as u16);
+            if self.encoder.is_none() {
+                let mut encoder = gif::Encoder::new(BufWriter::new(File::create(&self.path)?), width, height, &[])?;
+                encoder.set(gif::Repeat::Infinite)?;
+                self.encoder = Some(encoder);
+            }
+            let mut frame = gif::Frame::from_rgba_speed(width, height, &mut canvas.to_rgba8(), 10);
+            frame.delay = self.delay;
+            self.encoder.as_mut().unwrap().write_frame(&frame)?;
+        } else {
+            png::write_canvas(&canvas, File::create(self.frame_path(self.frames))?)?;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Number of frames recorded so far
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+}
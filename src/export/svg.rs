@@ -0,0 +1,56 @@
+use std::io::{self, Write, BufWriter};
+use std::fs::File;
+use std::path::Path;
+use draw::Drawing;
+use style::Colour;
+
+fn rgb(colour: Colour) -> String {
+    let channel = |c: f32| (c.max(0.).min(1.) * 255.).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(colour[0]), channel(colour[1]), channel(colour[2]))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn write<W: Write>(drawing: &Drawing, writer: W) -> io::Result<()> {
+    let mut w = BufWriter::new(writer);
+
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(w, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#, drawing.width, drawing.height)?;
+    writeln!(w, r#"<rect width="100%" height="100%" fill="{}"/>"#, rgb(drawing.background))?;
+
+    writeln!(w, r#"<g fill="none" stroke-width="1">"#)?;
+    for line in &drawing.lines {
+        let points: Vec<String> = line.points.iter().map(|p| format!("{:.2},{:.2}", p.x, p.y)).collect();
+        writeln!(w, r#"<polyline points="{}" stroke="{}" stroke-opacity="{}"/>"#, points.join(" "), rgb(line.colour), line.colour[3])?;
+    }
+    writeln!(w, "</g>")?;
+
+    writeln!(w, "<g>")?;
+    for square in &drawing.squares {
+        writeln!(w, r#"<rect x="{:.2}" y="{:.2}" width="{}" height="{}" fill="{}" fill-opacity="{}"/>"#,
+                 square.centre.x - square.size, square.centre.y - square.size, square.size * 2., square.size * 2.,
+                 rgb(square.colour), square.colour[3])?;
+    }
+    writeln!(w, "</g>")?;
+
+    if !drawing.labels.is_empty() {
+        writeln!(w, r#"<g font-family="Anonymous Pro, monospace">"#)?;
+        for label in &drawing.labels {
+            writeln!(w, r#"<text x="{:.2}" y="{:.2}" font-size="{}" fill="{}" fill-opacity="{}">{}</text>"#,
+                     label.position.x, label.position.y, label.size, rgb(label.colour), label.colour[3], escape(&label.text))?;
+        }
+        writeln!(w, "</g>")?;
+    }
+
+    writeln!(w, "</svg>")?;
+    w.flush()
+}
+
+pub fn write_file<P: AsRef<Path>>(drawing: &Drawing, path: P) -> io::Result<()> {
+    write(drawing, File::create(path)?)
+}
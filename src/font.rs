@@ -0,0 +1,58 @@
+//! The bundled label font, for exporters that embed or rasterize it themselves
+
+/// The TrueType file, built in so it is found wherever the program runs
+pub static BYTES: &'static [u8] = include_bytes!("../res/font/Anonymous Pro.ttf");
+/// PostScript name, as stored in the font's name table
+pub const NAME: &'static str = "AnonymousPro";
+
+/// Font-wide metrics from the `head` and `hhea` tables, in font units
+#[derive(Copy, Clone, Debug)]
+pub struct Metrics {
+    pub units_per_em: u16,
+    pub ascent: i16,
+    pub descent: i16,
+    /// Widest advance, which is every glyph's advance in a monospaced font
+    pub advance: u16,
+    /// xMin, yMin, xMax, yMax
+    pub bbox: [i16; 4],
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    if offset + 2 > bytes.len() {
+        return None;
+    }
+    Some((bytes[offset] as u16) << 8 | bytes[offset + 1] as u16)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some((u16_at(bytes, offset)? as u32) << 16 | u16_at(bytes, offset + 2)? as u32)
+}
+
+/// Offset of a table from the TrueType table directory
+fn table(bytes: &[u8], tag: &[u8]) -> Option<usize> {
+    let tables = u16_at(bytes, 4)? as usize;
+    (0..tables).map(|i| 12 + 16 * i)
+        .find(|&entry| bytes.get(entry..entry + 4) == Some(tag))
+        .and_then(|entry| u32_at(bytes, entry + 8))
+        .map(|offset| offset as usize)
+}
+
+impl Metrics {
+    pub fn parse(bytes: &[u8]) -> Option<Metrics> {
+        let head = table(bytes, b"head")?;
+        let hhea = table(bytes, b"hhea")?;
+        let signed = |offset| u16_at(bytes, offset).map(|v| v as i16);
+        Some(Metrics {
+            units_per_em: u16_at(bytes, head + 18)?,
+            ascent: signed(hhea + 4)?,
+            descent: signed(hhea + 6)?,
+            advance: u16_at(bytes, hhea + 10)?,
+            bbox: [signed(head + 36)?, signed(head + 38)?, signed(head + 40)?, signed(head + 42)?],
+        })
+    }
+
+    /// Scales a value in font units to thousandths of an em, as PDF wants
+    pub fn per_mille(&self, units: i16) -> i32 {
+        units as i32 * 1000 / self.units_per_em as i32
+    }
+}
@@ -0,0 +1,108 @@
+//! Shapes of edges: straight lines, curves for parallel edges, arcs for self-loops
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use cgmath::{Vector2, InnerSpace};
+use petgraph::Graph;
+use petgraph::graph::EdgeIndex;
+use graph::{Node, Edge};
+
+/// Points per curve or loop
+const SEGMENTS: usize = 16;
+
+/// Position of an edge among all edges joining the same pair of nodes,
+/// or the same node to itself
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Multiplicity {
+    pub rank: usize,
+    pub count: usize,
+}
+
+/// Multiplicity of every edge, indexed by edge index. Direction is ignored.
+pub fn multiplicities(graph: &Graph<Node, Edge>) -> Vec<Multiplicity> {
+    let mut ranks = Vec::with_capacity(graph.edge_count());
+    let mut counts = HashMap::new();
+    for e in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(e).unwrap();
+        let key = if a <= b { (a, b) } else { (b, a) };
+        let count = counts.entry(key).or_insert(0);
+        ranks.push((key, *count));
+        *count += 1;
+    }
+    ranks.into_iter().map(|(key, rank)| {
+        Multiplicity { rank: rank, count: counts[&key] }
+    }).collect()
+}
+
+/// Summary of the edges which can't be drawn as single straight lines
+#[derive(Debug, Default)]
+pub struct MultiEdges {
+    pub self_loops: usize,
+    /// Pairs of distinct nodes joined by more than one edge
+    pub parallel_pairs: usize,
+    /// Most edges between any one pair
+    pub max_parallel: usize,
+}
+
+impl MultiEdges {
+    pub fn of(graph: &Graph<Node, Edge>) -> MultiEdges {
+        let mut summary = MultiEdges::default();
+        for (e, m) in graph.edge_indices().zip(multiplicities(graph)) {
+            let (a, b) = graph.edge_endpoints(e).unwrap();
+            if a == b {
+                summary.self_loops += 1;
+            } else if m.count > 1 {
+                // count each pair once, by its first edge
+                if m.rank == 0 {
+                    summary.parallel_pairs += 1;
+                }
+                summary.max_parallel = summary.max_parallel.max(m.count);
+            }
+        }
+        summary
+    }
+}
+
+/// Polyline for an edge.
+///
+/// Single edges are straight. Parallel edges bow out to either side of the
+/// line between their nodes, and self-loops are circles of `loop_radius`
+/// fanned out around their node.
+pub fn edge_path(graph: &Graph<Node, Edge>, e: EdgeIndex, m: Multiplicity, loop_radius: f32) -> Vec<Vector2<f32>> {
+    let (a, b) = graph.edge_endpoints(e).unwrap();
+    let (p, q) = (graph[a].pos, graph[b].pos);
+
+    if a == b {
+        let angle = PI / 4. + 2. * PI * m.rank as f32 / m.count as f32;
+        // later loops on the same node are slightly larger, so they stay apart
+        let radius = loop_radius * (1. + 0.25 * m.rank as f32);
+        let centre = p + Vector2::new(angle.cos(), angle.sin()) * radius;
+        return (0..SEGMENTS + 1).map(|i| {
+            let t = angle + PI + 2. * PI * i as f32 / SEGMENTS as f32;
+            centre + Vector2::new(t.cos(), t.sin()) * radius
+        }).collect();
+    }
+
+    if m.count == 1 {
+        return vec![p, q];
+    }
+
+    // bend relative to a fixed orientation of the pair, so that edges
+    // in opposite directions don't land on the same curve
+    let (start, end) = if a < b { (p, q) } else { (q, p) };
+    let diff = end - start;
+    let length = diff.magnitude();
+    if length == 0. {
+        return vec![p, q];
+    }
+    let normal = Vector2::new(-diff.y, diff.x) / length;
+    let offset = (m.rank as f32 - (m.count - 1) as f32 / 2.) * 0.2 * length;
+    // control point twice as far out as the apex of the curve
+    let control = start + diff / 2. + normal * offset * 2.;
+
+    (0..SEGMENTS + 1).map(|i| {
+        let t = i as f32 / SEGMENTS as f32;
+        let u = 1. - t;
+        start * (u * u) + control * (2. * u * t) + end * (t * t)
+    }).collect()
+}
@@ -3,6 +3,7 @@ extern crate glium_text_rusttype as glium_text;
 
 use cgmath::Matrix4;
 use gl::cgtraits::AsUniform;
+use font;
 
 use self::glium_text::TextSystem;
 use self::glium_text::FontTexture;
@@ -11,8 +12,6 @@ use glium::Vertex;
 use glium::index::Index;
 use std::error::Error;
 
-use std::path::Path;
-use std::fs::File;
 
 #[derive(Copy, Clone)]
 pub struct Vertex3D {
@@ -26,6 +25,15 @@ pub struct Offset {
 }
 implement_vertex!(Offset, offset);
 
+/// A node drawn by `compile_node_program`: where, how large and what colour
+#[derive(Copy, Clone)]
+pub struct Instance {
+    pub offset: [f32; 3],
+    pub scale: f32,
+    pub rgba: [f32; 4],
+}
+implement_vertex!(Instance, offset, scale, rgba);
+
 pub fn make_square(display: glium::Display) -> Result<glium::VertexBuffer<Vertex3D>, glium::vertex::BufferCreationError> {
 
     let vertex1 = Vertex3D { position: [ -1.0,  -1.0, 0.0] };
@@ -65,8 +73,12 @@ pub struct TextDrawer {
 
 impl<'a> TextDrawer {
     pub fn println(&self, line: &'a str, frame: &mut glium::Frame, mvp: &Matrix4<f32>) {
+        self.print(line, frame, mvp, [1.0, 1.0, -1.0, 1.0]);
+    }
+
+    pub fn print(&self, line: &'a str, frame: &mut glium::Frame, mvp: &Matrix4<f32>, rgba: [f32; 4]) {
         let string = glium_text::TextDisplay::new(&self.system, &self.font, line);
-        glium_text::draw(&string, &self.system, frame, mvp.as_uniform(), (1.0, 1.0, -1.0, 1.0));
+        glium_text::draw(&string, &self.system, frame, mvp.as_uniform(), (rgba[0], rgba[1], rgba[2], rgba[3]));
     }
 
 }
@@ -74,7 +86,7 @@ impl<'a> TextDrawer {
 pub fn init_text(display: glium::Display, fontsize: u32) -> Result<TextDrawer, ()> {
     Ok( TextDrawer {
         system: TextSystem::new(&display),
-        font: FontTexture::new(&display, File::open(&Path::new("res/font/Anonymous Pro.ttf")).unwrap(), fontsize, FontTexture::ascii_character_list()).unwrap(),
+        font: FontTexture::new(&display, font::BYTES, fontsize, FontTexture::ascii_character_list()).unwrap(),
         fontsize: fontsize,
         line: 0,
     } )
@@ -110,4 +122,64 @@ pub fn compile_debug_program(display: glium::Display) -> Result<glium::Program,
     glium::Program::from_source(&display, vertex_shader_src, fragment_shader_src, None)
 }
 
+/// Lines through the `offset` of each vertex, in one colour
+pub fn compile_line_program(display: glium::Display) -> Result<glium::Program, glium::ProgramCreationError> {
+
+    let vertex_shader_src = r#"
+        #version 140
+
+        in vec3 offset;
+        uniform mat4 mvp;
+
+        void main() {
+            gl_Position = mvp * vec4(offset, 1.0);
+        }
+    "#;
+
+    let fragment_shader_src = r#"
+        #version 140
+
+        out vec4 color;
+
+        uniform vec4 rgba;
+
+        void main() {
+            color = rgba;
+        }
+    "#;
+
+    glium::Program::from_source(&display, vertex_shader_src, fragment_shader_src, None)
+}
+
+/// Like the debug program, but sized and coloured per instance
+pub fn compile_node_program(display: glium::Display) -> Result<glium::Program, glium::ProgramCreationError> {
+
+    let vertex_shader_src = r#"
+        #version 140
+
+        in vec3 position;
+        in vec3 offset;
+        in float scale;
+        in vec4 rgba;
+        uniform mat4 mvp;
+        out vec4 colour;
 
+        void main() {
+            colour = rgba;
+            gl_Position = mvp * vec4(position * 2.0 * scale + offset, 1.0);
+        }
+    "#;
+
+    let fragment_shader_src = r#"
+        #version 140
+
+        in vec4 colour;
+        out vec4 color;
+
+        void main() {
+            color = colour;
+        }
+    "#;
+
+    glium::Program::from_source(&display, vertex_shader_src, fragment_shader_src, None)
+}
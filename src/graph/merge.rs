@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use petgraph::graph::EdgeIndex;
+use graph::Network;
+
+/// Combines networks into one, unifying nodes by name.
+///
+/// Edges with the same endpoints and interaction are treated as the same edge,
+/// regardless of direction, and record every input they appear in. Repeated
+/// edges within a single input stay separate.
+pub fn merge(inputs: Vec<(String, Network)>) -> Network {
+    let mut merged = Network::new();
+    let mut existing: HashMap<(String, String, String), Vec<EdgeIndex>> = HashMap::new();
+
+    for (source, (name, network)) in inputs.into_iter().enumerate() {
+        merged.sources.push(name);
+        // how many of each existing edge this input has matched so far
+        let mut claimed: HashMap<(String, String, String), usize> = HashMap::new();
+
+        for v in network.graph.node_indices() {
+            let node = &network.graph[v];
+            let index = merged.node(&node.name);
+            if node.placed && !merged.graph[index].placed {
+                merged.graph[index].pos = node.pos;
+                merged.graph[index].placed = true;
+            }
+        }
+
+        for e in network.graph.edge_indices() {
+            let (a, b) = network.graph.edge_endpoints(e).unwrap();
+            let (a, b) = (&network.graph[a].name, &network.graph[b].name);
+            let interaction = &network.graph[e].interaction;
+            let key = if a <= b {
+                (a.clone(), b.clone(), interaction.clone())
+            } else {
+                (b.clone(), a.clone(), interaction.clone())
+            };
+
+            let used = claimed.entry(key.clone()).or_insert(0);
+            let edges = existing.entry(key).or_insert(Vec::new());
+            if *used < edges.len() {
+                merged.graph[edges[*used]].sources.push(source);
+            } else {
+                let index = merged.add_edge(a, interaction, b);
+                merged.graph[index].sources.push(source);
+                edges.push(index);
+            }
+            *used += 1;
+        }
+    }
+
+    merged
+}
+
+/// Edge counts for one input of a merged network
+#[derive(Debug)]
+pub struct SourceSummary {
+    pub name: String,
+    pub edges: usize,
+    /// Edges found in this input only
+    pub unique: usize,
+    /// Edges also found in at least one other input
+    pub shared: usize,
+}
+
+impl SourceSummary {
+    pub fn of(network: &Network) -> Vec<SourceSummary> {
+        let mut summaries: Vec<SourceSummary> = network.sources.iter().map(|name| {
+            SourceSummary { name: name.clone(), edges: 0, unique: 0, shared: 0 }
+        }).collect();
+
+        for edge in network.graph.raw_edges().iter().map(|e| &e.weight) {
+            for &source in &edge.sources {
+                let summary = &mut summaries[source];
+                summary.edges += 1;
+                if edge.sources.len() == 1 {
+                    summary.unique += 1;
+                } else {
+                    summary.shared += 1;
+                }
+            }
+        }
+        summaries
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use cgmath::{Vector2, Zero};
+use petgraph::Graph;
+use petgraph::graph::{NodeIndex, EdgeIndex};
+
+pub use self::merge::{merge, SourceSummary};
+pub use self::subgraph::{part, induced};
+
+pub mod merge;
+pub mod search;
+pub mod subgraph;
+
+/// A named vertex along with its layout state
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub name: String,
+    pub pos: Vector2<f32>,
+    pub disp: Vector2<f32>,
+    /// True once the node has a position, read from a file or scattered for
+    /// the layout, rather than the origin every node starts at
+    pub placed: bool,
+    /// Set once communities have been detected, see `analysis::community`
+    pub community: Option<usize>,
+}
+
+impl Node {
+    pub fn new(name: &str) -> Node {
+        Node {
+            name: name.to_string(),
+            pos: Vector2::zero(),
+            disp: Vector2::zero(),
+            placed: false,
+            community: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edge {
+    pub interaction: String,
+    /// Indices into `Network::sources` of the files containing this edge
+    pub sources: Vec<usize>,
+}
+
+impl Edge {
+    pub fn new(interaction: &str) -> Edge {
+        Edge {
+            interaction: interaction.to_string(),
+            sources: Vec::new(),
+        }
+    }
+}
+
+/// Interaction graph with a lookup from node name to index
+#[derive(Clone, Debug)]
+pub struct Network {
+    pub graph: Graph<Node, Edge>,
+    pub map: HashMap<String, NodeIndex>,
+    /// Names of the files merged into this network, empty if it came from just one
+    pub sources: Vec<String>,
+}
+
+impl Network {
+    pub fn new() -> Network {
+        Network {
+            graph: Graph::new(),
+            map: HashMap::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the named node, inserting it if needed
+    pub fn node(&mut self, name: &str) -> NodeIndex {
+        if let Some(&index) = self.map.get(name) {
+            return index;
+        }
+        let index = self.graph.add_node(Node::new(name));
+        self.map.insert(name.to_string(), index);
+        index
+    }
+
+    pub fn add_edge(&mut self, source: &str, interaction: &str, target: &str) -> EdgeIndex {
+        let a = self.node(source);
+        let b = self.node(target);
+        self.graph.add_edge(a, b, Edge::new(interaction))
+    }
+
+    /// True if every node already has a position, so no layout is needed
+    pub fn is_placed(&self) -> bool {
+        self.graph.node_count() > 0 && self.graph.node_indices().all(|v| self.graph[v].placed)
+    }
+}
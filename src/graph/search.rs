@@ -0,0 +1,54 @@
+use std::fmt;
+use petgraph::Graph;
+use petgraph::graph::NodeIndex;
+use regex::Regex;
+use graph::{Node, Edge};
+
+/// What node names to look for, written as the name itself, a prefix ending
+/// in `*`, or a regular expression between slashes, eg. `/^BRCA[12]$/`
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Exact(String),
+    Prefix(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn parse(query: &str) -> Result<Pattern, String> {
+        let query = query.trim();
+        if query.len() > 1 && query.starts_with('/') && query.ends_with('/') {
+            let expression = &query[1..query.len() - 1];
+            Regex::new(expression).map(Pattern::Regex).map_err(|e| format!("invalid regular expression '{}': {}", expression, e))
+        } else if query.ends_with('*') {
+            Ok(Pattern::Prefix(query[..query.len() - 1].to_string()))
+        } else if query.is_empty() {
+            Err("nothing to find".to_string())
+        } else {
+            Ok(Pattern::Exact(query.to_string()))
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match *self {
+            Pattern::Exact(ref text) => name == text,
+            Pattern::Prefix(ref text) => name.starts_with(text.as_str()),
+            Pattern::Regex(ref regex) => regex.is_match(name),
+        }
+    }
+
+    /// Nodes whose names match, in index order
+    pub fn find(&self, graph: &Graph<Node, Edge>) -> Vec<NodeIndex> {
+        graph.node_indices().filter(|&v| self.matches(&graph[v].name)).collect()
+    }
+}
+
+impl fmt::Display for Pattern {
+    /// The pattern as it would be written
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Pattern::Exact(ref text) => write!(f, "{}", text),
+            Pattern::Prefix(ref text) => write!(f, "{}*", text),
+            Pattern::Regex(ref regex) => write!(f, "/{}/", regex.as_str()),
+        }
+    }
+}
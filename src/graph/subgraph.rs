@@ -0,0 +1,25 @@
+use petgraph::Graph;
+use graph::{Node, Edge};
+
+/// The given nodes, by index, and those of the given edges between them,
+/// keeping positions, communities and sources
+pub fn part(graph: &Graph<Node, Edge>, nodes: &[bool], edges: &[bool]) -> Graph<Node, Edge> {
+    let mut part = Graph::new();
+    let mut index = vec![None; graph.node_count()];
+    for v in graph.node_indices() {
+        if nodes[v.index()] {
+            index[v.index()] = Some(part.add_node(graph[v].clone()));
+        }
+    }
+    for (edge, &kept) in graph.raw_edges().iter().zip(edges) {
+        if let (true, Some(a), Some(b)) = (kept, index[edge.source().index()], index[edge.target().index()]) {
+            part.add_edge(a, b, edge.weight.clone());
+        }
+    }
+    part
+}
+
+/// The subgraph induced by some nodes, by index: them and every edge between them
+pub fn induced(graph: &Graph<Node, Edge>, nodes: &[bool]) -> Graph<Node, Edge> {
+    part(graph, nodes, &vec![true; graph.edge_count()])
+}
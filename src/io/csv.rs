@@ -0,0 +1,114 @@
+//! Comma separated edge lists.
+//!
+//! Columns are `source,target[,interaction]` unless a header row names them,
+//! a row with only one field declares a lone node.
+
+use graph::Network;
+use io::LoadError;
+
+/// Splits a row into fields, honouring double quotes
+fn fields(row: &str) -> Result<Vec<String>, (usize, &'static str)> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = row.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' if quoted => {
+                // a doubled quote is a literal one
+                if chars.peek().map(|&(_, c)| c) == Some('"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            },
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            },
+            '"' => return Err((i, "misplaced quote")),
+            ',' if !quoted => {
+                fields.push(field.trim().to_string());
+                field.clear();
+            },
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err((row.len(), "unterminated quote"));
+    }
+    fields.push(field.trim().to_string());
+    Ok(fields)
+}
+
+pub fn parse(text: &str) -> Result<Network, LoadError> {
+    let mut network = Network::new();
+    let (mut source, mut target, mut interaction) = (0, 1, Some(2));
+    // a byte order mark isn't part of the first field
+    let text = text.trim_start_matches('\u{feff}');
+    // only the first row with anything in it may be a header
+    let mut first = true;
+
+    for (number, row) in text.lines().enumerate() {
+        let line = number + 1;
+        if row.trim().is_empty() {
+            continue;
+        }
+        let fields = fields(row).map_err(|(i, message)| LoadError::at(line, i + 1, message))?;
+
+        if first {
+            first = false;
+            let names: Vec<String> = fields.iter().map(|f| f.to_lowercase()).collect();
+            let column = |wanted: &[&str]| names.iter().position(|n| wanted.contains(&n.as_str()));
+            if let (Some(s), Some(t)) = (column(&["source", "from", "node1"]), column(&["target", "to", "node2"])) {
+                source = s;
+                target = t;
+                interaction = column(&["interaction", "type", "label"]);
+                continue;
+            }
+        }
+
+        if fields.len() == 1 {
+            network.node(&fields[0]);
+            continue;
+        }
+        let field = |i: usize| fields.get(i).map(|f| f.as_str()).unwrap_or("");
+        if field(source).is_empty() || field(target).is_empty() {
+            return Err(LoadError::at(line, 1, "row is missing a source or target"));
+        }
+        let kind = interaction.map(|i| field(i)).unwrap_or("");
+        network.add_edge(field(source), kind, field(target));
+    }
+
+    Ok(network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use io::tests::edges;
+
+    #[test]
+    fn edges_and_lone_nodes() {
+        let network = parse("a,b,pp\n\"b, c\",\"say \"\"hi\"\"\"\nlone\n").unwrap();
+        assert_eq!(edges(&network), vec![
+            ("a".to_string(), "pp".to_string(), "b".to_string()),
+            ("b, c".to_string(), "".to_string(), "say \"hi\"".to_string()),
+        ]);
+        assert_eq!(network.graph.node_count(), 5);
+    }
+
+    #[test]
+    fn header_after_blank_rows_and_a_byte_order_mark() {
+        let network = parse("\u{feff}\n  \nType,To,From\npp,b,a\n").unwrap();
+        assert_eq!(edges(&network), vec![("a".to_string(), "pp".to_string(), "b".to_string())]);
+        assert_eq!(network.graph.node_count(), 2);
+    }
+
+    #[test]
+    fn byte_order_mark_before_a_header() {
+        let network = parse("\u{feff}source,target\na,b\n").unwrap();
+        assert_eq!(edges(&network), vec![("a".to_string(), "".to_string(), "b".to_string())]);
+    }
+}
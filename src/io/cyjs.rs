@@ -0,0 +1,139 @@
+//! Cytoscape.js JSON, as written by `cy.json()` and Cytoscape's `.cyjs` export.
+//!
+//! Cytoscape places y downwards, so y is negated on the way in and out.
+
+use std::io::{self, Read, BufReader, Write, BufWriter};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use cgmath::Vector2;
+use petgraph::Graph;
+use serde_json;
+use graph::{Network, Node, Edge};
+use io::LoadError;
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    elements: Elements,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Elements {
+    #[serde(default)]
+    nodes: Vec<Element<NodeData>>,
+    #[serde(default)]
+    edges: Vec<Element<EdgeData>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Element<D> {
+    data: D,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<Position>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NodeData {
+    id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EdgeData {
+    id: String,
+    source: String,
+    target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    interaction: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+pub fn read<R: Read>(reader: R) -> Result<Network, LoadError> {
+    let document: Document = serde_json::from_reader(reader)?;
+    let mut network = Network::new();
+
+    // edges refer to nodes by id, which is not necessarily the name
+    let mut names = HashMap::new();
+    for element in &document.elements.nodes {
+        let name = element.data.name.clone().unwrap_or(element.data.id.clone());
+        let v = network.node(&name);
+        if let Some(ref position) = element.position {
+            network.graph[v].pos = Vector2::new(position.x, -position.y);
+            network.graph[v].placed = true;
+        }
+        names.insert(element.data.id.clone(), name);
+    }
+
+    for element in &document.elements.edges {
+        let data = &element.data;
+        let source = names.get(&data.source).unwrap_or(&data.source).clone();
+        let target = names.get(&data.target).unwrap_or(&data.target).clone();
+        let interaction = data.interaction.clone().unwrap_or(String::new());
+        network.add_edge(&source, &interaction, &target);
+    }
+
+    Ok(network)
+}
+
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Network, LoadError> {
+    read(BufReader::new(File::open(path)?))
+}
+
+pub fn write<W: Write>(graph: &Graph<Node, Edge>, writer: W) -> io::Result<()> {
+    let nodes = graph.node_indices().map(|v| {
+        let node = &graph[v];
+        Element {
+            data: NodeData {
+                id: format!("n{}", v.index()),
+                name: Some(node.name.clone()),
+            },
+            position: if node.placed { Some(Position { x: node.pos.x, y: -node.pos.y }) } else { None },
+        }
+    }).collect();
+
+    let edges = graph.edge_indices().map(|e| {
+        let (a, b) = graph.edge_endpoints(e).unwrap();
+        Element {
+            data: EdgeData {
+                id: format!("e{}", e.index()),
+                source: format!("n{}", a.index()),
+                target: format!("n{}", b.index()),
+                interaction: Some(graph[e].interaction.clone()),
+            },
+            position: None,
+        }
+    }).collect();
+
+    let document = Document {
+        elements: Elements { nodes: nodes, edges: edges },
+    };
+    let mut writer = BufWriter::new(writer);
+    serde_json::to_writer_pretty(&mut writer, &document)?;
+    writer.flush()
+}
+
+pub fn write_file<P: AsRef<Path>>(graph: &Graph<Node, Edge>, path: P) -> io::Result<()> {
+    write(graph, File::create(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use io::tests::{sample, edges, nodes};
+
+    #[test]
+    fn round_trip() {
+        let network = sample();
+        let mut written = Vec::new();
+        write(&network.graph, &mut written).unwrap();
+        let read = read(&written[..]).unwrap();
+        assert_eq!(edges(&read), edges(&network));
+        assert_eq!(nodes(&read), nodes(&network));
+    }
+}
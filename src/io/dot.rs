@@ -0,0 +1,462 @@
+//! Graphviz DOT. Subgraphs are flattened, and `pos` attributes are read as positions.
+
+use std::io::{self, Write, BufWriter};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use cgmath::Vector2;
+use petgraph::Graph;
+use graph::{Network, Node, Edge};
+use io::LoadError;
+use io::scan::Scanner;
+use style::{self, Colour, Style};
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    /// An id, and whether it was quoted, which stops it being a keyword
+    Id(String, bool),
+    Edge,
+    Open,
+    Close,
+    OpenAttr,
+    CloseAttr,
+    Equals,
+    Separator,
+    Port,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match *self {
+            Token::Id(ref s, false) => format!("'{}'", s),
+            Token::Id(ref s, true) => format!("\"{}\"", s),
+            Token::Edge => "an edge operator".to_string(),
+            Token::Open => "'{'".to_string(),
+            Token::Close => "'}'".to_string(),
+            Token::OpenAttr => "'['".to_string(),
+            Token::CloseAttr => "']'".to_string(),
+            Token::Equals => "'='".to_string(),
+            Token::Separator => "a separator".to_string(),
+            Token::Port => "':'".to_string(),
+        }
+    }
+}
+
+struct Lexer<'a> {
+    scanner: Scanner<'a>,
+    peeked: Option<(Token, usize, usize)>,
+    /// Position of the last token taken, for errors about it
+    last: (usize, usize),
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Lexer<'a> {
+        Lexer {
+            scanner: Scanner::new(text),
+            peeked: None,
+            last: (1, 1),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<&Token>, LoadError> {
+        if self.peeked.is_none() {
+            self.peeked = self.lex()?;
+        }
+        Ok(self.peeked.as_ref().map(|p| &p.0))
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, LoadError> {
+        self.peek()?;
+        Ok(self.peeked.take().map(|(token, line, column)| {
+            self.last = (line, column);
+            token
+        }))
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), LoadError> {
+        match self.next()? {
+            Some(ref t) if *t == token => Ok(()),
+            Some(t) => Err(self.error(format!("expected {}, found {}", token.describe(), t.describe()))),
+            None => Err(self.error(format!("expected {}, found end of file", token.describe()))),
+        }
+    }
+
+    fn error<S: Into<String>>(&self, message: S) -> LoadError {
+        match self.peeked {
+            Some((_, line, column)) => LoadError::at(line, column, message),
+            None => LoadError::at(self.last.0, self.last.1, message),
+        }
+    }
+
+    fn skip_comments(&mut self) -> Result<(), LoadError> {
+        loop {
+            self.scanner.skip_whitespace();
+            match self.scanner.peek() {
+                // preprocessor style lines
+                Some('#') if self.scanner.column == 1 => self.scanner.skip_line(),
+                Some('/') => {
+                    let (line, column) = (self.scanner.line, self.scanner.column);
+                    self.scanner.next();
+                    match self.scanner.next() {
+                        Some('/') => self.scanner.skip_line(),
+                        Some('*') => {
+                            let mut last = ' ';
+                            loop {
+                                match self.scanner.next() {
+                                    Some('/') if last == '*' => break,
+                                    Some(c) => last = c,
+                                    None => return Err(LoadError::at(line, column, "unterminated comment")),
+                                }
+                            }
+                        },
+                        _ => return Err(LoadError::at(line, column, "unexpected '/'")),
+                    }
+                },
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn lex(&mut self) -> Result<Option<(Token, usize, usize)>, LoadError> {
+        self.skip_comments()?;
+        let (line, column) = (self.scanner.line, self.scanner.column);
+        let c = match self.scanner.peek() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        let token = match c {
+            '{' => { self.scanner.next(); Token::Open },
+            '}' => { self.scanner.next(); Token::Close },
+            '[' => { self.scanner.next(); Token::OpenAttr },
+            ']' => { self.scanner.next(); Token::CloseAttr },
+            '=' => { self.scanner.next(); Token::Equals },
+            ';' | ',' => { self.scanner.next(); Token::Separator },
+            ':' => { self.scanner.next(); Token::Port },
+            '"' => {
+                self.scanner.next();
+                let mut s = String::new();
+                loop {
+                    match self.scanner.next() {
+                        Some('"') => break,
+                        Some('\\') => match self.scanner.next() {
+                            Some('"') => s.push('"'),
+                            Some('\n') => (),
+                            Some(c) => { s.push('\\'); s.push(c); },
+                            None => (),
+                        },
+                        Some(c) => s.push(c),
+                        None => return Err(LoadError::at(line, column, "unterminated string")),
+                    }
+                }
+                Token::Id(s, true)
+            },
+            '<' => {
+                // HTML strings nest angle brackets
+                self.scanner.next();
+                let mut depth = 1;
+                let mut s = String::new();
+                loop {
+                    match self.scanner.next() {
+                        Some('>') if depth == 1 => break,
+                        Some(c) => {
+                            match c {
+                                '<' => depth += 1,
+                                '>' => depth -= 1,
+                                _ => (),
+                            }
+                            s.push(c);
+                        },
+                        None => return Err(LoadError::at(line, column, "unterminated HTML string")),
+                    }
+                }
+                Token::Id(s, true)
+            },
+            '-' => {
+                // either an edge operator or a negative number
+                self.scanner.next();
+                match self.scanner.peek() {
+                    Some('-') | Some('>') => {
+                        self.scanner.next();
+                        Token::Edge
+                    },
+                    _ => Token::Id(format!("-{}", self.scanner.take_while(|c| c.is_digit(10) || c == '.')), false),
+                }
+            },
+            c if is_id_char(c) => Token::Id(self.scanner.take_while(is_id_char), false),
+            c => return Err(LoadError::at(line, column, format!("unexpected character '{}'", c))),
+        };
+        Ok(Some((token, line, column)))
+    }
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || !c.is_ascii()
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    network: Network,
+    edge_defaults: HashMap<String, String>,
+}
+
+impl<'a> Parser<'a> {
+    fn id(&mut self) -> Result<String, LoadError> {
+        match self.lexer.next()? {
+            Some(Token::Id(s, _)) => Ok(s),
+            Some(t) => Err(self.lexer.error(format!("expected an identifier, found {}", t.describe()))),
+            None => Err(self.lexer.error("expected an identifier, found end of file")),
+        }
+    }
+
+    /// An unquoted id, lowercased as keywords are case insensitive
+    fn keyword(&mut self) -> Result<String, LoadError> {
+        match self.lexer.next()? {
+            Some(Token::Id(s, false)) => Ok(s.to_lowercase()),
+            Some(t) => Err(self.lexer.error(format!("expected a keyword, found {}", t.describe()))),
+            None => Err(self.lexer.error("expected a keyword, found end of file")),
+        }
+    }
+
+    /// Parses any number of `[a=b, ...]` lists
+    fn attributes(&mut self) -> Result<HashMap<String, String>, LoadError> {
+        let mut attributes = HashMap::new();
+        while self.lexer.peek()? == Some(&Token::OpenAttr) {
+            self.lexer.next()?;
+            loop {
+                match self.lexer.peek()? {
+                    Some(&Token::CloseAttr) => {
+                        self.lexer.next()?;
+                        break;
+                    },
+                    Some(&Token::Separator) => {
+                        self.lexer.next()?;
+                    },
+                    _ => {
+                        let key = self.id()?;
+                        self.lexer.expect(Token::Equals)?;
+                        let value = self.id()?;
+                        attributes.insert(key, value);
+                    },
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// Parses statements up to the closing brace, returning the nodes they mention
+    fn statements(&mut self) -> Result<Vec<String>, LoadError> {
+        let mut mentioned = Vec::new();
+        loop {
+            match self.lexer.peek()? {
+                Some(&Token::Close) => {
+                    self.lexer.next()?;
+                    return Ok(mentioned);
+                },
+                Some(&Token::Separator) => {
+                    self.lexer.next()?;
+                },
+                None => return Err(self.lexer.error("missing '}'")),
+                _ => {
+                    let nodes = self.statement()?;
+                    mentioned.extend(nodes);
+                },
+            }
+        }
+    }
+
+    /// A node id or subgraph, as used on either side of an edge
+    fn operand(&mut self) -> Result<Vec<String>, LoadError> {
+        let subgraph = match self.lexer.peek()? {
+            Some(&Token::Open) => true,
+            Some(&Token::Id(ref s, false)) => s.to_lowercase() == "subgraph",
+            _ => false,
+        };
+
+        if !subgraph {
+            let id = self.id()?;
+            // ports are irrelevant here
+            while self.lexer.peek()? == Some(&Token::Port) {
+                self.lexer.next()?;
+                self.id()?;
+            }
+            return Ok(vec![id]);
+        }
+
+        if self.lexer.peek()? != Some(&Token::Open) {
+            self.lexer.next()?;
+            if let Some(&Token::Id(..)) = self.lexer.peek()? {
+                self.id()?;
+            }
+        }
+        self.lexer.expect(Token::Open)?;
+        self.statements()
+    }
+
+    fn statement(&mut self) -> Result<Vec<String>, LoadError> {
+        let keyword = match self.lexer.peek()? {
+            Some(&Token::Id(ref s, false)) => s.to_lowercase(),
+            _ => String::new(),
+        };
+        match keyword.as_str() {
+            "graph" | "node" => {
+                self.lexer.next()?;
+                self.attributes()?;
+                return Ok(Vec::new());
+            },
+            "edge" => {
+                self.lexer.next()?;
+                let attributes = self.attributes()?;
+                self.edge_defaults.extend(attributes);
+                return Ok(Vec::new());
+            },
+            _ => (),
+        }
+
+        let first = self.operand()?;
+
+        // `a = b` sets a graph attribute
+        if self.lexer.peek()? == Some(&Token::Equals) {
+            self.lexer.next()?;
+            self.id()?;
+            return Ok(Vec::new());
+        }
+
+        let mut chain = vec![first];
+        while self.lexer.peek()? == Some(&Token::Edge) {
+            self.lexer.next()?;
+            chain.push(self.operand()?);
+        }
+        let attributes = self.attributes()?;
+
+        if chain.len() == 1 {
+            for name in &chain[0] {
+                let v = self.network.node(name);
+                if let Some(pos) = attributes.get("pos") {
+                    let pos = parse_pos(pos).ok_or(self.lexer.error(format!("invalid pos \"{}\"", pos)))?;
+                    self.network.graph[v].pos = pos;
+                    self.network.graph[v].placed = true;
+                }
+            }
+        } else {
+            let interaction = attributes.get("interaction")
+                .or(attributes.get("label"))
+                .or(self.edge_defaults.get("interaction"))
+                .or(self.edge_defaults.get("label"))
+                .cloned()
+                .unwrap_or(String::new());
+            for pair in chain.windows(2) {
+                for source in &pair[0] {
+                    for target in &pair[1] {
+                        self.network.add_edge(source, &interaction, target);
+                    }
+                }
+            }
+        }
+
+        Ok(chain.into_iter().flat_map(|nodes| nodes).collect())
+    }
+}
+
+/// Reads `"x,y"` with an optional trailing `!`, in points with y pointing up
+fn parse_pos(pos: &str) -> Option<Vector2<f32>> {
+    let mut parts = pos.trim_end_matches('!').split(',');
+    let x = parts.next().and_then(|x| x.trim().parse().ok());
+    let y = parts.next().and_then(|y| y.trim().parse().ok());
+    match (x, y) {
+        (Some(x), Some(y)) => Some(Vector2::new(x, y)),
+        _ => None,
+    }
+}
+
+pub fn parse(text: &str) -> Result<Network, LoadError> {
+    let mut parser = Parser {
+        lexer: Lexer::new(text),
+        network: Network::new(),
+        edge_defaults: HashMap::new(),
+    };
+
+    let mut keyword = parser.keyword()?;
+    if keyword == "strict" {
+        keyword = parser.keyword()?;
+    }
+    if keyword != "graph" && keyword != "digraph" {
+        return Err(LoadError::at(1, 1, format!("expected 'graph' or 'digraph', found '{}'", keyword)));
+    }
+    if let Some(&Token::Id(..)) = parser.lexer.peek()? {
+        parser.id()?;
+    }
+    parser.lexer.expect(Token::Open)?;
+    parser.statements()?;
+
+    Ok(parser.network)
+}
+
+/// Quotes an id. Graphviz keeps other backslashes as they are, so only quotes are escaped.
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('"', "\\\""))
+}
+
+fn hex(colour: Colour) -> String {
+    let channel = |c: f32| (c.max(0.).min(1.) * 255.).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(colour[0]), channel(colour[1]), channel(colour[2]))
+}
+
+/// Writes a digraph with every placed node pinned at its position, ready
+/// for `neato -n`, and edges coloured by interaction type
+pub fn write<W: Write>(graph: &Graph<Node, Edge>, style: &Style, writer: W) -> io::Result<()> {
+    let mut w = BufWriter::new(writer);
+    let types = style::interaction_colours(graph, style);
+
+    writeln!(w, "digraph {{")?;
+    writeln!(w, "  node [shape=box style=filled color=\"{}\"];", hex(style.node))?;
+    // legend
+    for &(ref interaction, colour) in &types {
+        writeln!(w, "  // {}: {}", interaction, hex(colour))?;
+    }
+    let colours: HashMap<String, Colour> = types.into_iter().collect();
+    for v in graph.node_indices() {
+        let node = &graph[v];
+        if node.placed {
+            writeln!(w, "  {} [pos=\"{},{}!\"];", quote(&node.name), node.pos.x, node.pos.y)?;
+        } else {
+            writeln!(w, "  {};", quote(&node.name))?;
+        }
+    }
+    for e in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(e).unwrap();
+        let edge = &graph[e];
+        writeln!(w, "  {} -> {} [interaction={} color=\"{}\"];", quote(&graph[a].name), quote(&graph[b].name),
+                 quote(&edge.interaction), hex(colours[&edge.interaction]))?;
+    }
+    writeln!(w, "}}")?;
+    w.flush()
+}
+
+pub fn write_file<P: AsRef<Path>>(graph: &Graph<Node, Edge>, style: &Style, path: P) -> io::Result<()> {
+    write(graph, style, File::create(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use io::tests::{sample, edges, nodes};
+
+    #[test]
+    fn round_trip() {
+        let network = sample();
+        let mut written = Vec::new();
+        write(&network.graph, &Style::default(), &mut written).unwrap();
+        let read = parse(&String::from_utf8(written).unwrap()).unwrap();
+        assert_eq!(edges(&read), edges(&network));
+        assert_eq!(nodes(&read), nodes(&network));
+    }
+
+    #[test]
+    fn quoted_keywords_are_node_names() {
+        let network = parse(r#"digraph { "graph" -> "node" [interaction=pp]; "edge" [pos="1,2!"]; graph [rankdir=LR]; }"#).unwrap();
+        assert_eq!(edges(&network), vec![("graph".to_string(), "pp".to_string(), "node".to_string())]);
+        assert_eq!(nodes(&network)[2], ("edge".to_string(), Some((1., 2.))));
+        assert_eq!(network.graph.node_count(), 3);
+    }
+}
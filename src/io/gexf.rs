@@ -0,0 +1,59 @@
+//! GEXF with the viz extension, so Gephi opens the layout as drawn.
+//!
+//! Gephi places y upwards like the layout, so positions are written unchanged.
+
+use std::io::{self, Write, BufWriter};
+use std::fs::File;
+use std::path::Path;
+use petgraph::Graph;
+use graph::{Node, Edge};
+use io::escape;
+use draw::Drawing;
+use style::Colour;
+
+fn colour(colour: Colour) -> String {
+    let channel = |c: f32| (c.max(0.).min(1.) * 255.).round() as u8;
+    format!(r#"<viz:color r="{}" g="{}" b="{}" a="{}"/>"#, channel(colour[0]), channel(colour[1]), channel(colour[2]), colour[3])
+}
+
+/// Writes nodes with their positions, sizes and colours, and edges with
+/// their interaction and colour. The sizes and colours are those of `drawing`,
+/// which must have every node and edge in index order.
+pub fn write<W: Write>(graph: &Graph<Node, Edge>, drawing: &Drawing, writer: W) -> io::Result<()> {
+    let mut w = BufWriter::new(writer);
+
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(w, r#"<gexf xmlns="http://www.gexf.net/1.3" xmlns:viz="http://www.gexf.net/1.3/viz" version="1.3">"#)?;
+    writeln!(w, r#"<graph mode="static" defaultedgetype="directed">"#)?;
+    writeln!(w, r#"<attributes class="edge"><attribute id="interaction" title="interaction" type="string"/></attributes>"#)?;
+
+    writeln!(w, "<nodes>")?;
+    for (v, square) in graph.node_indices().zip(&drawing.squares) {
+        let node = &graph[v];
+        let position = if node.placed {
+            format!(r#"<viz:position x="{}" y="{}" z="0"/>"#, node.pos.x, node.pos.y)
+        } else {
+            String::new()
+        };
+        writeln!(w, r#"<node id="n{}" label="{}">{}<viz:size value="{}"/>{}</node>"#,
+                 v.index(), escape(&node.name), position, square.size, colour(square.colour))?;
+    }
+    writeln!(w, "</nodes>")?;
+
+    writeln!(w, "<edges>")?;
+    for (e, line) in graph.edge_indices().zip(&drawing.lines) {
+        let (a, b) = graph.edge_endpoints(e).unwrap();
+        let edge = &graph[e];
+        writeln!(w, r#"<edge id="e{}" source="n{}" target="n{}" label="{}"><attvalues><attvalue for="interaction" value="{}"/></attvalues>{}</edge>"#,
+                 e.index(), a.index(), b.index(), escape(&edge.interaction), escape(&edge.interaction), colour(line.colour))?;
+    }
+    writeln!(w, "</edges>")?;
+
+    writeln!(w, "</graph>")?;
+    writeln!(w, "</gexf>")?;
+    w.flush()
+}
+
+pub fn write_file<P: AsRef<Path>>(graph: &Graph<Node, Edge>, drawing: &Drawing, path: P) -> io::Result<()> {
+    write(graph, drawing, File::create(path)?)
+}
@@ -0,0 +1,163 @@
+//! Graph Modelling Language, as written by Cytoscape, yEd and igraph.
+
+use std::collections::HashMap;
+use cgmath::Vector2;
+use graph::Network;
+use io::LoadError;
+use io::scan::Scanner;
+
+enum Value {
+    Number(f64),
+    Str(String),
+    List(Vec<Entry>),
+}
+
+struct Entry {
+    key: String,
+    value: Value,
+    line: usize,
+    column: usize,
+}
+
+impl Entry {
+    fn error<S: Into<String>>(&self, message: S) -> LoadError {
+        LoadError::at(self.line, self.column, message)
+    }
+}
+
+fn find<'a>(list: &'a [Entry], key: &str) -> Option<&'a Value> {
+    list.iter().find(|e| e.key == key).map(|e| &e.value)
+}
+
+fn as_text(value: &Value) -> Option<String> {
+    match *value {
+        Value::Str(ref s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::List(_) => None,
+    }
+}
+
+/// Parses key value pairs until the end of input, or a `]` if nested
+fn parse_list(scanner: &mut Scanner, nested: bool) -> Result<Vec<Entry>, LoadError> {
+    let mut list = Vec::new();
+    loop {
+        scanner.skip_whitespace();
+        let (line, column) = (scanner.line, scanner.column);
+        match scanner.peek() {
+            None if nested => return Err(scanner.error("unclosed '['")),
+            None => return Ok(list),
+            Some(']') if nested => {
+                scanner.next();
+                return Ok(list);
+            },
+            Some('#') => {
+                scanner.skip_line();
+                continue;
+            },
+            Some(c) if c.is_alphabetic() || c == '_' => (),
+            Some(c) => return Err(scanner.error(format!("expected a key, found '{}'", c))),
+        }
+
+        let key = scanner.take_while(|c| c.is_alphanumeric() || c == '_');
+        scanner.skip_whitespace();
+        let value = match scanner.peek() {
+            Some('[') => {
+                scanner.next();
+                Value::List(parse_list(scanner, true)?)
+            },
+            Some('"') => {
+                scanner.next();
+                let s = scanner.take_while(|c| c != '"');
+                if scanner.next().is_none() {
+                    return Err(LoadError::at(line, column, format!("unterminated string for '{}'", key)));
+                }
+                Value::Str(unescape(&s))
+            },
+            Some(c) if c.is_digit(10) || c == '-' || c == '+' || c == '.' => {
+                let (line, column) = (scanner.line, scanner.column);
+                let s = scanner.take_while(|c| c.is_digit(10) || "-+.eE".contains(c));
+                Value::Number(s.parse().map_err(|_| LoadError::at(line, column, format!("invalid number '{}'", s)))?)
+            },
+            Some(c) => return Err(scanner.error(format!("expected a value for '{}', found '{}'", key, c))),
+            None => return Err(scanner.error(format!("missing value for '{}'", key))),
+        };
+        list.push(Entry { key: key, value: value, line: line, column: column });
+    }
+}
+
+/// GML strings use HTML character entities instead of backslash escapes
+fn unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+pub fn parse(text: &str) -> Result<Network, LoadError> {
+    let mut scanner = Scanner::new(text);
+    let top = parse_list(&mut scanner, false)?;
+    let graph = match top.iter().find(|e| e.key == "graph") {
+        Some(&Entry { value: Value::List(ref list), .. }) => list,
+        Some(entry) => return Err(entry.error("'graph' must be a list")),
+        None => return Err(scanner.error("no 'graph' found")),
+    };
+
+    let mut network = Network::new();
+    let mut names = HashMap::new();
+    for entry in graph.iter().filter(|e| e.key == "node") {
+        let node = match entry.value {
+            Value::List(ref list) => list,
+            _ => return Err(entry.error("'node' must be a list")),
+        };
+        let id = find(node, "id").and_then(as_text).ok_or(entry.error("node without an id"))?;
+        let name = find(node, "label").or(find(node, "name")).and_then(as_text).unwrap_or(id.clone());
+        let v = network.node(&name);
+
+        // Cytoscape and yEd write screen coordinates, y pointing down
+        if let Some(&Value::List(ref graphics)) = find(node, "graphics") {
+            if let (Some(&Value::Number(x)), Some(&Value::Number(y))) = (find(graphics, "x"), find(graphics, "y")) {
+                network.graph[v].pos = Vector2::new(x as f32, -y as f32);
+                network.graph[v].placed = true;
+            }
+        }
+        names.insert(id, name);
+    }
+
+    for entry in graph.iter().filter(|e| e.key == "edge") {
+        let edge = match entry.value {
+            Value::List(ref list) => list,
+            _ => return Err(entry.error("'edge' must be a list")),
+        };
+        let endpoint = |key| {
+            let id = find(edge, key).and_then(as_text).ok_or(entry.error(format!("edge without a {}", key)))?;
+            names.get(&id).cloned().ok_or(entry.error(format!("edge {} '{}' is not a node", key, id)))
+        };
+        let source = endpoint("source")?;
+        let target = endpoint("target")?;
+        let interaction = find(edge, "interaction").or(find(edge, "label")).and_then(as_text).unwrap_or(String::new());
+        network.add_edge(&source, &interaction, &target);
+    }
+
+    Ok(network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use io::tests::{edges, nodes};
+
+    #[test]
+    fn nodes_edges_and_graphics() {
+        let text = r#"graph [
+  directed 1
+  node [ id 1 label "a" graphics [ x 10.5 y 20 ] ]
+  node [ id 2 label "b" ]
+  edge [ source 1 target 2 interaction "pp" ]
+]"#;
+        let network = parse(text).unwrap();
+        assert_eq!(edges(&network), vec![("a".to_string(), "pp".to_string(), "b".to_string())]);
+        assert_eq!(nodes(&network)[1], ("b".to_string(), None));
+        // y points down in GML
+        assert_eq!(nodes(&network)[0], ("a".to_string(), Some((10.5, -20.))));
+    }
+}
@@ -0,0 +1,271 @@
+//! GraphML, including node positions from plain `x`/`y` keys or yFiles geometry.
+
+extern crate xml;
+
+use std::io::{self, Write, BufWriter};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use cgmath::Vector2;
+use petgraph::Graph;
+use self::xml::reader::{EventReader, XmlEvent};
+use self::xml::attribute::OwnedAttribute;
+use self::xml::common::Position;
+use graph::{Network, Node, Edge};
+use io::{LoadError, escape};
+use draw::Drawing;
+use style::Colour;
+
+/// What a `<data>` element holds, decided by the `attr.name` of its key
+#[derive(Copy, Clone, PartialEq)]
+enum Key {
+    Name,
+    X,
+    Y,
+    Interaction,
+    Other,
+}
+
+struct PendingNode {
+    id: String,
+    name: Option<String>,
+    x: Option<f32>,
+    y: Option<f32>,
+}
+
+struct PendingEdge {
+    source: String,
+    target: String,
+    interaction: Option<String>,
+}
+
+fn attribute<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes.iter()
+        .find(|a| a.name.local_name == name)
+        .map(|a| a.value.as_str())
+}
+
+pub fn parse(text: &str) -> Result<Network, LoadError> {
+    let mut parser = EventReader::new(text.as_bytes());
+
+    let mut keys = HashMap::new();
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+
+    let mut node: Option<PendingNode> = None;
+    let mut edge: Option<PendingEdge> = None;
+    // key of the <data> element or yFiles label being read, and its text
+    let mut data: Option<Key> = None;
+    let mut characters = String::new();
+
+    loop {
+        let event = parser.next().map_err(|e| {
+            let position = e.position();
+            LoadError::at(position.row as usize + 1, position.column as usize + 1, e.msg().to_string())
+        })?;
+        let position = parser.position();
+        let error = |message: String| {
+            LoadError::at(position.row as usize + 1, position.column as usize + 1, message)
+        };
+
+        match event {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                match name.local_name.as_str() {
+                    "key" => {
+                        let id = attribute(&attributes, "id").ok_or(error("key without an id".to_string()))?;
+                        let key = match attribute(&attributes, "attr.name").unwrap_or("") {
+                            "name" | "label" | "shared name" => Key::Name,
+                            "x" => Key::X,
+                            "y" => Key::Y,
+                            "interaction" | "type" => Key::Interaction,
+                            _ => Key::Other,
+                        };
+                        keys.insert(id.to_string(), key);
+                    },
+                    "node" => {
+                        let id = attribute(&attributes, "id").ok_or(error("node without an id".to_string()))?;
+                        node = Some(PendingNode { id: id.to_string(), name: None, x: None, y: None });
+                    },
+                    "edge" => {
+                        let source = attribute(&attributes, "source").ok_or(error("edge without a source".to_string()))?;
+                        let target = attribute(&attributes, "target").ok_or(error("edge without a target".to_string()))?;
+                        edge = Some(PendingEdge { source: source.to_string(), target: target.to_string(), interaction: None });
+                    },
+                    "data" => {
+                        let key = attribute(&attributes, "key").unwrap_or("");
+                        data = Some(*keys.get(key).unwrap_or(&Key::Other));
+                        characters.clear();
+                    },
+                    // yFiles stores geometry in screen coordinates, y pointing down
+                    "Geometry" => {
+                        if let Some(ref mut node) = node {
+                            let number = |name| attribute(&attributes, name).and_then(|v| v.parse::<f32>().ok());
+                            let (w, h) = (number("width").unwrap_or(0.), number("height").unwrap_or(0.));
+                            node.x = number("x").map(|x| x + w / 2.);
+                            node.y = number("y").map(|y| -(y + h / 2.));
+                        }
+                    },
+                    "NodeLabel" | "EdgeLabel" => {
+                        data = Some(if node.is_some() { Key::Name } else { Key::Interaction });
+                        characters.clear();
+                    },
+                    _ => (),
+                }
+            },
+
+            XmlEvent::Characters(s) | XmlEvent::CData(s) => {
+                if data.is_some() {
+                    characters.push_str(&s);
+                }
+            },
+
+            XmlEvent::EndElement { name } => {
+                match name.local_name.as_str() {
+                    "data" | "NodeLabel" | "EdgeLabel" => {
+                        let value = characters.trim().to_string();
+                        match (data.take(), node.as_mut(), edge.as_mut()) {
+                            (Some(Key::Name), Some(node), _) => {
+                                if !value.is_empty() {
+                                    node.name = Some(value);
+                                }
+                            },
+                            (Some(Key::X), Some(node), _) => {
+                                node.x = Some(value.parse().map_err(|_| error(format!("invalid x coordinate '{}'", value)))?);
+                            },
+                            (Some(Key::Y), Some(node), _) => {
+                                node.y = Some(value.parse().map_err(|_| error(format!("invalid y coordinate '{}'", value)))?);
+                            },
+                            (Some(Key::Interaction), _, Some(edge)) | (Some(Key::Name), _, Some(edge)) => {
+                                if !value.is_empty() {
+                                    edge.interaction = Some(value);
+                                }
+                            },
+                            _ => (),
+                        }
+                    },
+                    "node" => nodes.extend(node.take()),
+                    "edge" => edges.extend(edge.take()),
+                    _ => (),
+                }
+            },
+
+            XmlEvent::EndDocument => break,
+
+            _ => (),
+        }
+    }
+
+    let mut network = Network::new();
+    let mut names = HashMap::new();
+    for node in nodes {
+        let name = node.name.unwrap_or(node.id.clone());
+        let v = network.node(&name);
+        if let (Some(x), Some(y)) = (node.x, node.y) {
+            network.graph[v].pos = Vector2::new(x, y);
+            network.graph[v].placed = true;
+        }
+        names.insert(node.id, name);
+    }
+    for edge in edges {
+        // edges may refer to nodes that were never declared
+        let source = names.get(&edge.source).unwrap_or(&edge.source).clone();
+        let target = names.get(&edge.target).unwrap_or(&edge.target).clone();
+        network.add_edge(&source, &edge.interaction.unwrap_or(String::new()), &target);
+    }
+
+    Ok(network)
+}
+
+fn hex(colour: Colour) -> String {
+    let channel = |c: f32| (c.max(0.).min(1.) * 255.).round() as u8;
+    format!("#{:02X}{:02X}{:02X}{:02X}", channel(colour[0]), channel(colour[1]), channel(colour[2]), channel(colour[3]))
+}
+
+/// Writes the layout both as plain `x`/`y` keys, for Cytoscape and Gephi,
+/// and as yFiles shapes with colours, for yEd. The shapes' sizes and colours
+/// are those of `drawing`, which must have every node and edge in index order.
+pub fn write<W: Write>(graph: &Graph<Node, Edge>, drawing: &Drawing, writer: W) -> io::Result<()> {
+    let mut w = BufWriter::new(writer);
+
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(w, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:y="http://www.yworks.com/xml/graphml">"#)?;
+    writeln!(w, r#"<key id="name" for="node" attr.name="name" attr.type="string"/>"#)?;
+    writeln!(w, r#"<key id="x" for="node" attr.name="x" attr.type="double"/>"#)?;
+    writeln!(w, r#"<key id="y" for="node" attr.name="y" attr.type="double"/>"#)?;
+    writeln!(w, r#"<key id="interaction" for="edge" attr.name="interaction" attr.type="string"/>"#)?;
+    writeln!(w, r#"<key id="nodegraphics" for="node" yfiles.type="nodegraphics"/>"#)?;
+    writeln!(w, r#"<key id="edgegraphics" for="edge" yfiles.type="edgegraphics"/>"#)?;
+    writeln!(w, r#"<graph id="G" edgedefault="directed">"#)?;
+
+    for (v, square) in graph.node_indices().zip(&drawing.squares) {
+        let node = &graph[v];
+        let name = escape(&node.name);
+        let (size, fill) = (square.size * 2., hex(square.colour));
+        // only nodes with a position get one, so unplaced nodes are laid out on loading
+        if node.placed {
+            writeln!(w, r#"<node id="n{}"><data key="name">{}</data><data key="x">{}</data><data key="y">{}</data>"#,
+                     v.index(), name, node.pos.x, node.pos.y)?;
+            // yFiles wants the top left corner, with y pointing down
+            writeln!(w, r#"<data key="nodegraphics"><y:ShapeNode><y:Geometry x="{}" y="{}" width="{}" height="{}"/><y:Fill color="{}"/><y:NodeLabel>{}</y:NodeLabel><y:Shape type="rectangle"/></y:ShapeNode></data></node>"#,
+                     node.pos.x - size / 2., -node.pos.y - size / 2., size, size, fill, name)?;
+        } else {
+            writeln!(w, r#"<node id="n{}"><data key="name">{}</data>"#, v.index(), name)?;
+            writeln!(w, r#"<data key="nodegraphics"><y:ShapeNode><y:Fill color="{}"/><y:NodeLabel>{}</y:NodeLabel><y:Shape type="rectangle"/></y:ShapeNode></data></node>"#,
+                     fill, name)?;
+        }
+    }
+
+    for (e, line) in graph.edge_indices().zip(&drawing.lines) {
+        let (a, b) = graph.edge_endpoints(e).unwrap();
+        let edge = &graph[e];
+        writeln!(w, r#"<edge id="e{}" source="n{}" target="n{}"><data key="interaction">{}</data>"#,
+                 e.index(), a.index(), b.index(), escape(&edge.interaction))?;
+        writeln!(w, r#"<data key="edgegraphics"><y:PolyLineEdge><y:LineStyle color="{}" type="line" width="1.0"/></y:PolyLineEdge></data></edge>"#,
+                 hex(line.colour))?;
+    }
+
+    writeln!(w, "</graph>")?;
+    writeln!(w, "</graphml>")?;
+    w.flush()
+}
+
+pub fn write_file<P: AsRef<Path>>(graph: &Graph<Node, Edge>, drawing: &Drawing, path: P) -> io::Result<()> {
+    write(graph, drawing, File::create(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use draw::{self, Camera};
+    use style::Style;
+    use io::tests::{sample, edges, nodes};
+
+    fn written(network: &Network, sizes: Option<&[f32]>) -> String {
+        let ref graph = network.graph;
+        let mut drawing = Drawing::new(graph, &Style::default(), false, &Camera::fit(graph, 100., 100., 10.), false);
+        if let Some(sizes) = sizes {
+            drawing.size_nodes(sizes);
+        }
+        let mut written = Vec::new();
+        write(graph, &drawing, &mut written).unwrap();
+        String::from_utf8(written).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let network = sample();
+        let read = parse(&written(&network, None)).unwrap();
+        assert_eq!(edges(&read), edges(&network));
+        assert_eq!(nodes(&read), nodes(&network));
+    }
+
+    #[test]
+    fn shapes_sized_as_drawn() {
+        let network = sample();
+        let text = written(&network, Some(&[1., 0., 0., 0., 0.]));
+        let largest = draw::NODE_SIZE * draw::node_scale(1.) * 2.;
+        assert!(text.contains(&format!(r#"width="{}" height="{}""#, largest, largest)));
+        let smallest = draw::NODE_SIZE * draw::node_scale(0.) * 2.;
+        assert!(text.contains(&format!(r#"width="{}" height="{}""#, smallest, smallest)));
+    }
+}
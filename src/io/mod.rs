@@ -0,0 +1,244 @@
+extern crate flate2;
+
+use std::io::{self, Read};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+use self::flate2::read::GzDecoder;
+use serde_json;
+use petgraph::Graph;
+use graph::{Network, Node, Edge};
+use draw::Drawing;
+use style::Style;
+
+mod scan;
+pub mod sif;
+pub mod cyjs;
+pub mod graphml;
+pub mod dot;
+pub mod gml;
+pub mod csv;
+pub mod stream;
+pub mod gexf;
+pub mod tikz;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// Malformed input, line and column count from 1
+    Parse { line: usize, column: usize, message: String },
+}
+
+impl LoadError {
+    pub fn at<S: Into<String>>(line: usize, column: usize, message: S) -> LoadError {
+        LoadError::Parse { line: line, column: column, message: message.into() }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Io(ref e) => write!(f, "{}", e),
+            LoadError::Parse { line, column, ref message } => write!(f, "line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(e: serde_json::Error) -> LoadError {
+        if e.is_io() {
+            return LoadError::Io(e.into());
+        }
+        // serde_json appends the position to its messages itself
+        let message = e.to_string();
+        let message = message.split(" at line ").next().unwrap();
+        LoadError::at(e.line(), e.column(), message)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    Sif,
+    GraphMl,
+    Dot,
+    Gml,
+    Csv,
+    /// Cytoscape.js elements JSON
+    Json,
+}
+
+impl Format {
+    pub fn from_extension(ext: &str) -> Option<Format> {
+        match ext.to_lowercase().as_str() {
+            "sif" => Some(Format::Sif),
+            "graphml" | "xml" => Some(Format::GraphMl),
+            "dot" | "gv" => Some(Format::Dot),
+            "gml" => Some(Format::Gml),
+            "csv" => Some(Format::Csv),
+            "json" | "cyjs" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the first meaningful characters of the text
+    pub fn sniff(text: &str) -> Format {
+        // skip comment lines, which both DOT and GML permit
+        let start = text.lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with("//"))
+            .next()
+            .unwrap_or("");
+
+        let lower = start.to_lowercase();
+        if start.starts_with('<') {
+            Format::GraphMl
+        } else if start.starts_with('{') || start.starts_with('[') {
+            Format::Json
+        } else if lower.starts_with("digraph") || lower.starts_with("strict") {
+            Format::Dot
+        } else if lower.starts_with("graph") {
+            // `graph {` is DOT and `graph [` is GML, the bracket may be on a later line
+            match text.find(|c| c == '{' || c == '[') {
+                Some(i) if &text[i..i + 1] == "{" => Format::Dot,
+                _ => Format::Gml,
+            }
+        } else if lower.starts_with("creator") || lower.starts_with("version") {
+            Format::Gml
+        } else if start.contains(',') && !start.contains('\t') {
+            Format::Csv
+        } else {
+            Format::Sif
+        }
+    }
+
+    /// Format implied by a file name, ignoring any `.gz` suffix
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Format> {
+        let path = path.as_ref();
+        let path = if is_gzip_path(path) { Path::new(path.file_stem().unwrap()) } else { path };
+        path.extension().and_then(|e| e.to_str()).and_then(Format::from_extension)
+    }
+}
+
+fn is_gzip_path(path: &Path) -> bool {
+    path.extension().map(|e| e == "gz").unwrap_or(false)
+}
+
+/// Loads a network from a file, see `read`
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Network, LoadError> {
+    let path = path.as_ref();
+    read(File::open(path)?, Format::from_path(path))
+}
+
+/// Reads a network in any supported format, decompressing gzip input.
+///
+/// If no format is given, it is guessed from the content.
+pub fn read<R: Read>(mut reader: R, format: Option<Format>) -> Result<Network, LoadError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    // gzip magic number
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut inflated = Vec::new();
+        GzDecoder::new(&bytes[..]).read_to_end(&mut inflated)?;
+        bytes = inflated;
+    }
+
+    let text = decode(bytes)?;
+    let format = format.unwrap_or_else(|| Format::sniff(&text));
+    parse(&text, format)
+}
+
+/// Parses text already known to be in the given format
+pub fn parse(text: &str, format: Format) -> Result<Network, LoadError> {
+    match format {
+        Format::Sif => sif::read(text.as_bytes()),
+        Format::GraphMl => graphml::parse(text),
+        Format::Dot => dot::parse(text),
+        Format::Gml => gml::parse(text),
+        Format::Csv => csv::parse(text),
+        Format::Json => cyjs::read(text.as_bytes()),
+    }
+}
+
+fn decode(bytes: Vec<u8>) -> Result<String, LoadError> {
+    String::from_utf8(bytes).map_err(|e| {
+        let valid = &e.as_bytes()[..e.utf8_error().valid_up_to()];
+        let line = valid.iter().filter(|&&b| b == b'\n').count() + 1;
+        let column = valid.iter().rev().take_while(|&&b| b != b'\n').count() + 1;
+        LoadError::at(line, column, "invalid UTF-8")
+    })
+}
+
+/// Writes a graph with its layout, in the format named by the file's extension:
+/// `sif`, `cyjs`/`json`, `graphml`, `gexf`, `dot`/`gv` or `tex` for TikZ. `style` colours
+/// DOT and TikZ, while GraphML and GEXF take each node's size and colour and each edge's
+/// colour from `drawing`, which must have every node and edge of `graph` in index order.
+pub fn save<P: AsRef<Path>>(graph: &Graph<Node, Edge>, style: &Style, drawing: &Drawing, path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "sif" => sif::write_file(graph, path),
+        "cyjs" | "json" => cyjs::write_file(graph, path),
+        "graphml" => graphml::write_file(graph, drawing, path),
+        "gexf" => gexf::write_file(graph, drawing, path),
+        "dot" | "gv" => dot::write_file(graph, style, path),
+        "tex" => tikz::write_file(graph, style, tikz::SIZE, path),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "unknown format, use .sif, .cyjs, .graphml, .gexf, .dot or .tex")),
+    }
+}
+
+/// Escapes text for XML content and attribute values
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Vector2;
+    use graph::Network;
+
+    /// A network with awkward names, one unplaced node and a lone node
+    pub fn sample() -> Network {
+        let mut network = Network::new();
+        network.add_edge("a", "pp", "b c");
+        network.add_edge("b c", "pd", "\"q\" & <x>");
+        network.add_edge("graph", "pp", "a");
+        network.node("lone");
+        for (i, v) in network.graph.node_indices().enumerate() {
+            if i != 1 {
+                network.graph[v].pos = Vector2::new(i as f32 * 1.5, -2.25 * i as f32);
+                network.graph[v].placed = true;
+            }
+        }
+        network
+    }
+
+    /// Every edge as source, interaction and target, by edge index
+    pub fn edges(network: &Network) -> Vec<(String, String, String)> {
+        let ref graph = network.graph;
+        graph.raw_edges().iter().map(|e| {
+            (graph[e.source()].name.clone(), e.weight.interaction.clone(), graph[e.target()].name.clone())
+        }).collect()
+    }
+
+    /// Every node's name and position, if it has one, by node index
+    pub fn nodes(network: &Network) -> Vec<(String, Option<(f32, f32)>)> {
+        let ref graph = network.graph;
+        graph.node_indices().map(|v| {
+            let node = &graph[v];
+            (node.name.clone(), if node.placed { Some((node.pos.x, node.pos.y)) } else { None })
+        }).collect()
+    }
+}
@@ -0,0 +1,72 @@
+use std::str::Chars;
+use std::iter::Peekable;
+use io::LoadError;
+
+/// Character reader for the hand written parsers, tracks line and column for errors
+pub struct Scanner<'a> {
+    chars: Peekable<Chars<'a>>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(text: &'a str) -> Scanner<'a> {
+        Scanner {
+            chars: text.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<char> {
+        self.chars.peek().cloned()
+    }
+
+    pub fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        match c {
+            Some('\n') => {
+                self.line += 1;
+                self.column = 1;
+            },
+            Some(_) => self.column += 1,
+            None => (),
+        }
+        c
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.next();
+        }
+    }
+
+    /// Skips to the end of the current line
+    pub fn skip_line(&mut self) {
+        while let Some(c) = self.next() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    /// Consumes characters while `f` holds
+    pub fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> String {
+        let mut s = String::new();
+        while let Some(c) = self.peek() {
+            if !f(c) {
+                break;
+            }
+            s.push(c);
+            self.next();
+        }
+        s
+    }
+
+    pub fn error<S: Into<String>>(&self, message: S) -> LoadError {
+        LoadError::at(self.line, self.column, message)
+    }
+}
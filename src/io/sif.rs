@@ -0,0 +1,98 @@
+//! Simple interaction format, read here rather than with the `sifter` crate:
+//! that was a path dependency on a checkout beside this one, so the crate
+//! didn't build on its own, and its graph had nowhere to keep loaded
+//! positions, communities or which input each edge came from.
+
+use std::io::{self, BufRead, BufReader, Write, BufWriter};
+use std::fs::File;
+use std::path::Path;
+use petgraph::Graph;
+use graph::{Network, Node, Edge};
+use io::LoadError;
+
+/// Reads a simple interaction format file.
+///
+/// Each line is `source interaction target [target ...]`, or a lone node name.
+/// If a line contains tabs it is split on tabs only, so names may contain spaces.
+pub fn read<R: BufRead>(reader: R) -> Result<Network, LoadError> {
+    let mut network = Network::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        parse_line(&mut network, &line).map_err(|message| {
+            LoadError::at(number + 1, line.len() + 1, message)
+        })?;
+    }
+    Ok(network)
+}
+
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Network, LoadError> {
+    read(BufReader::new(File::open(path)?))
+}
+
+/// Adds the contents of a single SIF line to the network
+pub fn parse_line(network: &mut Network, line: &str) -> Result<(), String> {
+    let fields: Vec<&str> = if line.contains('\t') {
+        line.split('\t').map(|f| f.trim()).filter(|f| !f.is_empty()).collect()
+    } else {
+        line.split_whitespace().collect()
+    };
+
+    match fields.len() {
+        0 => (),
+        1 => { network.node(fields[0]); },
+        2 => return Err(format!("interaction '{}' has no target", fields[1])),
+        _ => {
+            for target in &fields[2..] {
+                network.add_edge(fields[0], fields[1], target);
+            }
+        },
+    }
+    Ok(())
+}
+
+pub fn write<W: Write>(graph: &Graph<Node, Edge>, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    for e in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(e).unwrap();
+        writeln!(writer, "{}\t{}\t{}", graph[a].name, graph[e].interaction, graph[b].name)?;
+    }
+    // nodes without edges still need a line of their own
+    for v in graph.node_indices() {
+        if graph.neighbors_undirected(v).next().is_none() {
+            writeln!(writer, "{}", graph[v].name)?;
+        }
+    }
+    writer.flush()
+}
+
+pub fn write_file<P: AsRef<Path>>(graph: &Graph<Node, Edge>, path: P) -> io::Result<()> {
+    write(graph, File::create(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use io::tests::{sample, edges, nodes};
+
+    #[test]
+    fn round_trip() {
+        let network = sample();
+        let mut written = Vec::new();
+        write(&network.graph, &mut written).unwrap();
+        let read = read(&written[..]).unwrap();
+        assert_eq!(edges(&read), edges(&network));
+        // SIF keeps no positions
+        let names = |network: &Network| nodes(network).into_iter().map(|n| n.0).collect::<Vec<_>>();
+        assert_eq!(names(&read), names(&network));
+    }
+
+    #[test]
+    fn several_targets_on_a_line() {
+        let network = read("a pp b c\nd\n".as_bytes()).unwrap();
+        assert_eq!(edges(&network), vec![
+            ("a".to_string(), "pp".to_string(), "b".to_string()),
+            ("a".to_string(), "pp".to_string(), "c".to_string()),
+        ]);
+        assert_eq!(network.graph.node_count(), 4);
+    }
+}
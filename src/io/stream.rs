@@ -0,0 +1,74 @@
+use std::io::{self, BufRead, BufReader};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use graph::Network;
+use io::LoadError;
+use io::sif;
+
+/// Lines handled per call to `poll`, so a fast producer can't stall drawing
+const BATCH: usize = 1000;
+
+/// SIF read line by line on a background thread, to be added to a live network
+pub struct SifStream {
+    receiver: Receiver<io::Result<String>>,
+    line: usize,
+    finished: bool,
+    /// Node and edge counts at the end of the last successful poll
+    seen: (usize, usize),
+}
+
+impl SifStream {
+    pub fn spawn<R: BufRead + Send + 'static>(reader: R) -> SifStream {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let failed = line.is_err();
+                // stop once nobody is listening
+                if sender.send(line).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        SifStream {
+            receiver: receiver,
+            line: 0,
+            finished: false,
+            seen: (0, 0),
+        }
+    }
+
+    pub fn stdin() -> SifStream {
+        SifStream::spawn(BufReader::new(io::stdin()))
+    }
+
+    /// True once the input has closed and every line has been handled
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Adds lines that have arrived since the last call to the network.
+    ///
+    /// Returns whether anything was added. A malformed line is returned as an
+    /// error, lines after it are picked up by the next call.
+    pub fn poll(&mut self, network: &mut Network) -> Result<bool, LoadError> {
+        for _ in 0..BATCH {
+            let line = match self.receiver.try_recv() {
+                Ok(line) => line?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.finished = true;
+                    break;
+                },
+            };
+            self.line += 1;
+            sif::parse_line(network, &line).map_err(|message| {
+                LoadError::at(self.line, line.len() + 1, message)
+            })?;
+        }
+        let counts = (network.graph.node_count(), network.graph.edge_count());
+        let changed = counts != self.seen;
+        self.seen = counts;
+        Ok(changed)
+    }
+}
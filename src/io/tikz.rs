@@ -0,0 +1,106 @@
+//! TikZ picture for LaTeX, as a standalone document.
+//!
+//! Nodes are placed at their layout positions scaled to centimetres, and
+//! each interaction type gets its own edge style to restyle in one place.
+
+use std::io::{self, Write, BufWriter};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use cgmath::Vector2;
+use petgraph::Graph;
+use graph::{Node, Edge};
+use geometry;
+use style::{self, Colour, Style};
+
+/// Width or height of the picture, whichever is larger, in centimetres
+pub const SIZE: f32 = 15.;
+
+/// Escapes characters TeX treats specially
+fn escape(text: &str) -> String {
+    let mut s = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => s.push_str("\\textbackslash{}"),
+            '^' => s.push_str("\\^{}"),
+            '~' => s.push_str("\\~{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                s.push('\\');
+                s.push(c);
+            },
+            _ => s.push(c),
+        }
+    }
+    s
+}
+
+fn rgb(colour: Colour) -> String {
+    format!("{{rgb,1:red,{:.3};green,{:.3};blue,{:.3}}}", colour[0], colour[1], colour[2])
+}
+
+/// Writes a picture `size` centimetres across its larger side
+pub fn write<W: Write>(graph: &Graph<Node, Edge>, style: &Style, size: f32, writer: W) -> io::Result<()> {
+    let mut w = BufWriter::new(writer);
+
+    let mut nodes = graph.node_indices().map(|v| graph[v].pos);
+    let (min, max) = match nodes.next() {
+        Some(first) => nodes.fold((first, first), |(min, max), p| {
+            (Vector2::new(min.x.min(p.x), min.y.min(p.y)), Vector2::new(max.x.max(p.x), max.y.max(p.y)))
+        }),
+        None => (Vector2::new(0., 0.), Vector2::new(0., 0.)),
+    };
+    let extent = (max.x - min.x).max(max.y - min.y);
+    let scale = if extent > 0. { size / extent } else { 1. };
+    let centre = (min + max) / 2.;
+
+    let types = style::interaction_colours(graph, style);
+    let styles: HashMap<&str, usize> = types.iter().enumerate().map(|(i, t)| (t.0.as_str(), i)).collect();
+
+    writeln!(w, "\\documentclass[tikz]{{standalone}}")?;
+    writeln!(w, "\\begin{{document}}")?;
+    writeln!(w, "\\begin{{tikzpicture}}[")?;
+    writeln!(w, "  vertex/.style={{rectangle, fill={}, inner sep=0pt, minimum size=2pt}},", rgb(style.node))?;
+    writeln!(w, "  name/.style={{font=\\tiny}},")?;
+    for (i, &(ref interaction, colour)) in types.iter().enumerate() {
+        writeln!(w, "  i{}/.style={{draw={}, thin}}, % {}", i, rgb(colour), interaction)?;
+    }
+    writeln!(w, "]")?;
+
+    for v in graph.node_indices() {
+        let node = &graph[v];
+        writeln!(w, "\\node[vertex, label={{[name]right:{{{}}}}}] (n{}) at ({:.3}, {:.3}) {{}};",
+                 escape(&node.name), v.index(), (node.pos.x - centre.x) * scale, (node.pos.y - centre.y) * scale)?;
+    }
+
+    let multiplicities = geometry::multiplicities(graph);
+    for e in graph.edge_indices() {
+        let (a, b) = graph.edge_endpoints(e).unwrap();
+        let m = multiplicities[e.index()];
+        let path = if a == b {
+            // fanned out like the window draws them
+            let angle = 45. + 360. * m.rank as f32 / m.count as f32;
+            format!("to[loop, out={:.0}, in={:.0}]", angle + 20., angle - 20.)
+        } else if m.count > 1 && 2 * m.rank + 1 != m.count {
+            // the same curve as geometry::edge_path, which bends relative to the lower index
+            let k = m.rank as f32 - (m.count - 1) as f32 / 2.;
+            let bend = (0.8 * k).atan().to_degrees() * if a < b { 1. } else { -1. };
+            if bend >= 0. {
+                format!("to[bend left={:.1}]", bend)
+            } else {
+                format!("to[bend right={:.1}]", -bend)
+            }
+        } else {
+            // single edges, and the middle one of an odd number, are straight
+            "--".to_string()
+        };
+        writeln!(w, "\\draw[i{}] (n{}) {} (n{});", styles[graph[e].interaction.as_str()], a.index(), path, b.index())?;
+    }
+
+    writeln!(w, "\\end{{tikzpicture}}")?;
+    writeln!(w, "\\end{{document}}")?;
+    w.flush()
+}
+
+pub fn write_file<P: AsRef<Path>>(graph: &Graph<Node, Edge>, style: &Style, size: f32, path: P) -> io::Result<()> {
+    write(graph, style, size, File::create(path)?)
+}
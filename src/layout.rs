@@ -0,0 +1,149 @@
+//! Fruchterman-Reingold force-directed placement
+
+use std::collections::{HashMap, HashSet};
+use rand::Rng;
+use rand::distributions::{Range, Sample};
+use cgmath::{Vector2, Zero, InnerSpace};
+use petgraph::Graph;
+use graph::{Node, Edge};
+
+pub struct ForceDirected {
+    /// Area the layout is computed for
+    pub width: f32,
+    pub height: f32,
+    pub iterations: usize,
+    pub iteration: usize,
+    /// Minimal distance between nodes
+    pub epsilon: f32,
+    /// Maximum displacement per iteration, as a fraction of the area's side
+    pub temperature: f32,
+    /// Pull of each node towards the centre of its community, relative to
+    /// an edge's, 0 for none
+    pub community_attraction: f32,
+}
+
+impl ForceDirected {
+    pub fn new(width: f32, height: f32) -> ForceDirected {
+        ForceDirected {
+            width: width,
+            height: height,
+            iterations: 50,
+            iteration: 0,
+            epsilon: 0.01,
+            temperature: 0.02,
+            community_attraction: 0.,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.iteration >= self.iterations
+    }
+
+    /// Runs the full number of iterations again, eg. after the graph grew
+    pub fn restart(&mut self) {
+        self.iteration = 0;
+    }
+
+    /// Marks the layout complete without moving anything
+    pub fn finish(&mut self) {
+        self.iteration = self.iterations;
+    }
+
+    /// Performs one iteration, does nothing once the layout is done
+    pub fn step(&mut self, graph: &mut Graph<Node, Edge>) {
+        if self.is_done() || graph.node_count() == 0 {
+            return;
+        }
+
+        let epsilon = self.epsilon;
+        let area = self.width * self.height;
+        let k = (area/graph.node_count() as f32).sqrt();
+        // in the paper f_a includes an x, I think it's supposed to be z
+        let f_a = |x: f32| { (x*x)/k };
+        let f_r = |x: f32| { (k*k)/x };
+        let temp = self.temperature * area.sqrt();
+
+        for v in graph.node_indices() {
+            graph[v].disp = Vector2::zero();
+            for u in graph.node_indices() {
+                if u != v {
+                    let diff = graph[v].pos - graph[u].pos;
+                    let magnitude = f32::max(diff.magnitude(), epsilon);
+                    graph[v].disp = graph[v].disp + (diff/magnitude) * f_r(magnitude);
+                }
+            }
+            // this didn't seem to be faster at all...
+            /*graph[v].disp = graph.raw_nodes().into_par_iter()
+                .fold(|| Vector2::zero(), |mut vector, u| {
+                    let diff = graph[v].pos - u.weight.pos;
+                    let magnitude = f32::max(diff.magnitude(), epsilon);
+                    vector = vector + (diff/magnitude) * f_r(magnitude);
+                    vector
+                }).sum();*/
+        }
+
+        // a self-loop exerts no force, and parallel edges attract only as
+        // much as a single edge would
+        let mut pairs = HashSet::new();
+        for e in graph.edge_indices() {
+            let (v, u) = graph.edge_endpoints(e).unwrap();
+            if v == u || !pairs.insert(if v < u { (v, u) } else { (u, v) }) {
+                continue;
+            }
+            let diff = graph[v].pos - graph[u].pos;
+            let magnitude = f32::max(diff.magnitude(), epsilon);
+            graph[v].disp = graph[v].disp - (diff/magnitude) * f_a(magnitude);
+            graph[u].disp = graph[u].disp + (diff/magnitude) * f_a(magnitude);
+        }
+
+        if self.community_attraction > 0. {
+            let mut centres: HashMap<usize, (Vector2<f32>, f32)> = HashMap::new();
+            for v in graph.node_indices() {
+                if let Some(c) = graph[v].community {
+                    let centre = centres.entry(c).or_insert((Vector2::zero(), 0.));
+                    centre.0 = centre.0 + graph[v].pos;
+                    centre.1 += 1.;
+                }
+            }
+            for v in graph.node_indices() {
+                if let Some(c) = graph[v].community {
+                    let (sum, count) = centres[&c];
+                    let diff = graph[v].pos - sum / count;
+                    let magnitude = f32::max(diff.magnitude(), epsilon);
+                    graph[v].disp = graph[v].disp - (diff/magnitude) * f_a(magnitude) * self.community_attraction;
+                }
+            }
+        }
+
+        for v in graph.node_indices() {
+            let magnitude = f32::max(graph[v].disp.magnitude(), epsilon);
+            graph[v].pos = graph[v].pos + (graph[v].disp / magnitude) * f32::min(magnitude, temp);
+            // uncomment the following to force nodes not to go beyond the layout area
+            //graph[v].pos.x = f32::min(self.width/2., f32::max(-self.width/2., graph[v].pos.x));
+            //graph[v].pos.y = f32::min(self.height/2., f32::max(-self.height/2., graph[v].pos.y));
+        }
+
+        self.iteration += 1;
+    }
+
+    /// Iterates until done
+    pub fn run(&mut self, graph: &mut Graph<Node, Edge>) {
+        while !self.is_done() {
+            self.step(graph);
+        }
+    }
+}
+
+/// Gives random starting positions to nodes from index `from` on,
+/// except those which were placed by the input file, and marks them placed
+pub fn scatter<R: Rng>(graph: &mut Graph<Node, Edge>, from: usize, rng: &mut R) {
+    let mut between_x = Range::new(-100., 100.);
+    let mut between_y = Range::new(-100., 100.);
+
+    for v in graph.node_indices().skip(from) {
+        if !graph[v].placed {
+            graph[v].pos = Vector2::new(between_x.sample(rng), between_y.sample(rng));
+            graph[v].placed = true;
+        }
+    }
+}
@@ -1,11 +1,33 @@
+//! Loading, laying out, analysing and exporting networks. The window and
+//! everything using OpenGL is behind the `viewer` feature, on by default;
+//! without it this is a plain library.
+
+#[cfg(feature = "viewer")]
 #[macro_use] extern crate glium;
 #[macro_use] extern crate serde_derive;
+#[cfg(feature = "viewer")]
 #[macro_use] extern crate conrod;
-mod support;
+#[cfg(feature = "viewer")]
+pub mod support;
 
 extern crate serde;
 extern crate serde_json;
 extern crate cgmath;
+extern crate petgraph;
+extern crate rand;
+extern crate regex;
 
+#[cfg(feature = "viewer")]
 pub mod core;
+#[cfg(feature = "viewer")]
 pub mod gl;
+pub mod graph;
+pub mod io;
+pub mod geometry;
+pub mod draw;
+pub mod font;
+pub mod export;
+pub mod layout;
+pub mod style;
+pub mod config;
+pub mod analysis;
@@ -1,296 +1,514 @@
 extern crate life;
-extern crate sifter;
 extern crate petgraph;
 extern crate rand;
+#[cfg(feature = "viewer")]
 #[macro_use] extern crate glium;
+#[cfg(feature = "viewer")]
+#[macro_use] extern crate conrod;
 extern crate cgmath;
 use std::env;
-use rand::distributions::{Range, Sample};
-use cgmath::{Matrix4, Vector2, Vector3, Vector4, Zero, InnerSpace};
+use std::fs;
+use std::process;
+use std::path::Path;
+use petgraph::Graph;
+use petgraph::graph::NodeIndex;
+use rand::{Rng, SeedableRng, StdRng};
 use life::*;
-use sifter::*;
-use glium::Surface;
-use life::core::Core;
-use life::gl::cgtraits::AsUniform;
+use life::config::Config;
+use life::graph::{Node, Edge};
+use life::analysis::community::Method;
+use life::analysis::cores::Filter;
+use life::analysis::ego::Ego;
+use life::analysis::paths;
+use life::layout::ForceDirected;
+
+mod cli;
+use cli::{Command, Options};
+
+#[cfg(feature = "viewer")]
+mod viewer;
+#[cfg(feature = "viewer")]
+use viewer::view;
+
+#[cfg(not(feature = "viewer"))]
+fn view(_: &Options, _: &Config) -> Result<(), String> {
+    Err("this build has no window, rebuild with the `viewer` feature".to_string())
+}
 
-fn main() {
-    let mut core = Core::initialize();
+/// Reads every input, `-` being stdin, and merges them if there are several
+fn load(inputs: &[String]) -> Result<graph::Network, String> {
+    let mut networks = Vec::new();
+    for input in inputs {
+        let result = if input == "-" {
+            io::read(std::io::stdin(), None)
+        } else {
+            io::load(input)
+        };
+        match result {
+            Ok(network) => networks.push((input.clone(), network)),
+            Err(e) => return Err(format!("{}: {}", input, e)),
+        }
+    }
 
-    // let life core handle the mainloop
+    if networks.len() == 1 {
+        return Ok(networks.pop().unwrap().1);
+    }
+    let network = graph::merge(networks);
+    for summary in graph::SourceSummary::of(&network) {
+        println!("{}: {} edges, {} unique, {} shared", summary.name, summary.edges, summary.unique, summary.shared);
+    }
+    Ok(network)
+}
 
-    let mut args = env::args();
-    args.next(); // consume first useless arg
-    let filename = match args.next() {
-        Some(arg) => arg,
-        None => panic!("Failed to open file"),
-    };
-    
-    let siffile = read_file(&filename).unwrap();
-    let mut mapped_graph = sif_to_petgraph(&siffile);
-    let ref mut graph = mapped_graph.graph;
-
-    let mut scale: f32 = 1.0;
-    let mut translation = Vector3::new(0., 0., 0.,);
-    let mut movement = Vector3::new(0., 0., 0.,);
-
-    let zero = glium::VertexBuffer::new(&core.window.clone_display(), &vec![gl::base::Vertex3D{position:[0.,0.,0.,]}]).unwrap();
-    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
-   
-        
-    let mut between_x = Range::new(-100., 100.);
-    let mut between_y = Range::new(-100., 100.);
-    let mut rng = rand::thread_rng();
-    
-    let mut square;
-    let mut nodes = {
-        let data = graph.node_indices().map(|index| {
-
-            let x = between_x.sample(&mut rng);
-            let y = between_y.sample(&mut rng);
-            graph[index].pos = Vector2::new(x, y);
-
-            gl::base::Offset {
-                offset: [x, y, 0.0],
-            }
-        }).collect::<Vec<_>>();
-        glium::vertex::VertexBuffer::dynamic(&core.window.clone_display(), &data).unwrap()
+/// The configuration file, or the defaults, with the command line's overrides
+fn settings(options: &Options) -> Result<Config, String> {
+    let mut config = match options.config {
+        Some(ref path) => Config::load(path).map_err(|e| format!("{}: {}", path, e))?,
+        None => Config::default(),
     };
+    if let Some((width, height)) = options.size {
+        config.layout.width = width;
+        config.layout.height = height;
+        config.window.width = width as u32;
+        config.window.height = height as u32;
+    }
+    if let Some(iterations) = options.iterations {
+        config.layout.iterations = iterations;
+    }
+    if let Some(attraction) = options.community_attraction {
+        config.layout.community_attraction = attraction;
+    }
+    Ok(config)
+}
 
-    let program = core.window.with_display(gl::base::compile_debug_program).unwrap();
-
-    let edges = { 
-        let mut edgeindices = Vec::new();
-        let mut colouredindices = Vec::new();
-        graph.edge_indices().for_each(|index| {
-            let ends = graph.edge_endpoints(index).unwrap();
-            if graph[index] == "OVERLAY" {
-                colouredindices.push(ends.0.index() as u32);
-                colouredindices.push(ends.1.index() as u32);
-            } else {
-                edgeindices.push(ends.0.index() as u32);
-                edgeindices.push(ends.1.index() as u32);
-            }
-        });
+fn rng(options: &Options) -> StdRng {
+    match options.seed {
+        Some(seed) => SeedableRng::from_seed(&[seed][..]),
+        None => StdRng::new().expect("no source of randomness"),
+    }
+}
 
-        (glium::IndexBuffer::new(&core.window.clone_display(), glium::index::PrimitiveType::LinesList, &edgeindices).unwrap(),
-        glium::IndexBuffer::new(&core.window.clone_display(), glium::index::PrimitiveType::LinesList, &colouredindices).unwrap())
+/// The layout as chosen on the command line, already finished if the
+/// input came with a complete layout
+fn layout_for(options: &Options, config: &Config, network: &graph::Network) -> ForceDirected {
+    let mut layout = match options.algorithm {
+        cli::Algorithm::FruchtermanReingold => config.layout.force_directed(),
     };
+    if network.is_placed() {
+        // a complete layout was loaded, don't disturb it
+        layout.finish();
+    }
+    layout
+}
 
+/// Node sizes for `--centrality`, scaled from 0 to 1
+fn node_sizes(options: &Options, graph: &Graph<Node, Edge>) -> Option<Vec<f32>> {
+    options.centrality.map(|c| analysis::centrality::normalise(&c.of(graph, options.samples, &mut rng(options))))
+}
 
-    let lineparams = glium::DrawParameters {
-        blend: glium::Blend::alpha_blending(),
-        //line_width: Some(2.),
-        //smooth: Some(glium::draw_parameters::Smooth::Nicest),
-        polygon_mode: glium::draw_parameters::PolygonMode::Line,
-        .. Default::default()
+/// Marks every node with its community found by `method`, or clears them without one
+fn mark_communities<R: Rng>(method: Option<Method>, resolution: f32, graph: &mut Graph<Node, Edge>, rng: &mut R) {
+    let found = method.map(|m| analysis::community::detect(graph, m, resolution, rng));
+    for v in graph.node_indices() {
+        graph[v].community = found.as_ref().map(|f| f[v.index()]);
+    }
+}
+
+/// Paths between `--from` and `--to`, none if they weren't given
+fn find_paths(options: &Options, network: &graph::Network) -> Result<Vec<paths::Path>, String> {
+    let (from, to) = match (&options.from, &options.to) {
+        (&Some(ref from), &Some(ref to)) => (from, to),
+        _ => return Ok(Vec::new()),
+    };
+    let node = |name: &String| network.map.get(name).cloned().ok_or(format!("no node named '{}'", name));
+    let (from, to) = (node(from)?, node(to)?);
+    let weight = |edge: &Edge| match options.weights {
+        Some(ref weights) => weights.iter().find(|w| w.0 == edge.interaction).map_or(1., |w| w.1),
+        None => 1.,
     };
 
+    let ref graph = network.graph;
+    Ok(if options.k > 1 {
+        paths::k_cheapest(graph, from, to, options.k, weight)
+    } else if options.weights.is_some() {
+        paths::cheapest(graph, from, to, weight).into_iter().collect()
+    } else {
+        paths::shortest(graph, from, to).into_iter().collect()
+    })
+}
 
-    // force-directed algorithm
-    let (mut w, mut h) = (800., 600.);
-    let area = w * h;
-    // random positions to graph are assigned within area
-    let k = (area/graph.node_count() as f32).sqrt();
-    // in the paper f_a includes an x, I think it's supposed to be z
-    let f_a = |x: f32| { (x*x)/k };
-    let f_r = |x: f32| { (k*k)/x };
-
-    let epsilon: f32 = 0.01; // minimal distance
-
-    let temp = 0.02 * area.sqrt();
-
-    let mut iteration = 0;
-    let iterations = 50;
-
-    let mut shutdown = false;
-    let display = core.window.clone_display();
-
-    let mut projection: Matrix4<f32> = Matrix4::from(cgmath::Ortho {
-        left: -w/2.,
-        right: w/2.,
-        bottom: -h/2.,
-        top: h/2.,
-        near: -1.0,
-        far: 1.0 });
-
-    let mut mousedown = false;
-    let (mut m_x, mut m_y) = (0.0, 0.0);
-    loop {
-
-        {
-            use glium::glutin::{DeviceEvent, WindowEvent, Event, ElementState, MouseButton, MouseScrollDelta};
-
-            core.window.events_loop.poll_events(|e| {
-                match e {
-                    Event::DeviceEvent { event, .. } => {
-                        match event {
-
-                            DeviceEvent::MouseWheel { delta } => {
-                                let (mut _x, mut y);
-                                match delta {
-                                    MouseScrollDelta::LineDelta(lx, ly) => {_x = lx; y = ly},
-                                    MouseScrollDelta::PixelDelta(lx, ly) => {_x = lx; y = ly},
-                                }
-                                scale += y / 100.;
-                                if scale < 1. {
-                                    scale = 1.;
-                                }
-                            },
-
-                            _ => (),
-                        }
-                    },
-
-                    Event::WindowEvent { event, .. } => match event {
-
-                        WindowEvent::CursorMoved { position, .. } => {
-                            if mousedown {
-                                movement.x += m_x - position.0 as f32;
-                                movement.y += m_y - position.1 as f32;
-                            }
-                            // this will always be set, and up to date
-                            m_x = position.0 as f32;
-                            m_y = position.1 as f32;
-                        },
-
-                        WindowEvent::Resized(x, y) => {
-
-                            mousedown = false;
-
-                            w = x as f32;
-                            h = y as f32;
-
-                            projection = Matrix4::from(cgmath::Ortho {
-                                left: -w/2.,
-                                right: w/2.,
-                                bottom: -h/2.,
-                                top: h/2.,
-                                near: -1.0,
-                                far: 1.0 });
-                        },
-
-                        WindowEvent::Closed => {
-                            shutdown = true;
-                        },
-
-                        WindowEvent::MouseInput { button, state, .. } => {
-                            match button {
-                                MouseButton::Left => {
-                                    match state {
-                                        ElementState::Pressed => {
-                                            if m_x > 0.0 && m_y > 0.0 {
-                                                mousedown = true;
-                                            }
-                                        },
-                                        ElementState::Released => {
-                                            mousedown = false;
-                                        },
-                                    }
-                                },
-                                _ => {},
-                            }
-                        },
-
-                        _ => (),
-
-                    },
-
-                    _ => (),
-
-                }
-            });
+/// The nodes and edges of some paths, if there are any
+fn highlight_paths(found: &[paths::Path]) -> Option<draw::Highlight> {
+    match found.len() {
+        0 => None,
+        _ => Some(draw::Highlight::new(found.iter().flat_map(|p| p.nodes.clone()), found.iter().flat_map(|p| p.edges.clone()))),
+    }
+}
 
+/// The nodes and edges of the paths asked for, if any
+fn path_highlight(options: &Options, network: &graph::Network) -> Result<Option<draw::Highlight>, String> {
+    let found = find_paths(options, network)?;
+    if found.is_empty() {
+        if let (&Some(ref from), &Some(ref to)) = (&options.from, &options.to) {
+            eprintln!("No path between {} and {}", from, to);
         }
+    }
+    Ok(highlight_paths(&found))
+}
 
-        translation.x -= movement.x;
-        translation.y += movement.y;
-        movement.x = 0.;
-        movement.y = 0.;
-
-        let mvp = projection * Matrix4::from_translation(translation) * Matrix4::from_scale(scale.powf(scale));
-        let edge_uniforms = uniform! { mvp: mvp.as_uniform(), rgba: Vector4::<f32>::new(0.0 as f32, 0.0, 0.0, 0.7).as_uniform()};
-        let edge_uniforms2 = uniform! { mvp: mvp.as_uniform(), rgba: Vector4::<f32>::new(1.0 as f32, 0.0, 0.0, 0.7).as_uniform()};
-        let node_uniforms = uniform! { mvp: mvp.as_uniform(), rgba: Vector4::<f32>::new(0.0 as f32, 0.6, 0.0, 0.0).as_uniform()};
-
-        if iteration < iterations {
-            for v in graph.node_indices() {
-                graph[v].disp = Vector2::zero();
-                for u in graph.node_indices() {
-                    if u != v {
-                        let diff = graph[v].pos - graph[u].pos;
-                        let magnitude = f32::max(diff.magnitude(), epsilon);
-                        graph[v].disp = graph[v].disp + (diff/magnitude) * f_r(magnitude);
-                    }
-                }
-                // this didn't seem to be faster at all...
-                /*graph[v].disp = graph.raw_nodes().into_par_iter()
-                    .fold(|| Vector2::zero(), |mut vector, u| {
-                        let diff = graph[v].pos - u.weight.pos;
-                        let magnitude = f32::max(diff.magnitude(), epsilon);
-                        vector = vector + (diff/magnitude) * f_r(magnitude);
-                        vector
-                    }).sum();*/
-            }
+/// Some nodes found, if there are any
+fn highlight_found(found: &[NodeIndex]) -> Option<draw::Highlight> {
+    match found.len() {
+        0 => None,
+        _ => Some(draw::Highlight::new(found.iter().cloned(), None)),
+    }
+}
 
-            for e in graph.edge_indices() {
-                let (v, u) = graph.edge_endpoints(e).unwrap();
-                let diff = graph[v].pos - graph[u].pos;
-                let magnitude = f32::max(diff.magnitude(), epsilon);
-                graph[v].disp = graph[v].disp - (diff/magnitude) * f_a(magnitude);
-                graph[u].disp = graph[u].disp + (diff/magnitude) * f_a(magnitude);
-            }
+fn join_highlights(a: Option<draw::Highlight>, b: Option<draw::Highlight>) -> Option<draw::Highlight> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.join(b)),
+        (a, b) => a.or(b),
+    }
+}
 
-            for v in graph.node_indices() {
-                let magnitude = f32::max(graph[v].disp.magnitude(), epsilon);
-                graph[v].pos = graph[v].pos + (graph[v].disp / magnitude) * f32::min(magnitude, temp);
-                // uncomment the following to force nodes not to go beyond window space
-                //graph[v].pos.x = f32::min(W/2., f32::max(-W/2., graph[v].pos.x));
-                //graph[v].pos.y = f32::min(H/2., f32::max(-H/2., graph[v].pos.y));
-            }
+/// The paths and the `--find` nodes asked for, if any
+fn highlight(options: &Options, network: &graph::Network) -> Result<Option<draw::Highlight>, String> {
+    let found = options.find.as_ref().map_or(Vec::new(), |pattern| pattern.find(&network.graph));
+    if let Some(ref pattern) = options.find {
+        match found.len() {
+            0 => eprintln!("No node matches {}", pattern),
+            n => println!("Nodes matching {}: {}", pattern, n),
+        }
+    }
+    Ok(join_highlights(path_highlight(options, network)?, highlight_found(&found)))
+}
 
-            if iteration == iterations - 1 {
-                println!("Layout complete!");
-            }
+/// The neighbourhood of the `--ego` nodes, if any were given
+fn ego(options: &Options, network: &graph::Network) -> Result<Option<Ego>, String> {
+    if options.ego.is_empty() {
+        return Ok(None);
+    }
+    let seeds = options.ego.iter()
+        .map(|name| network.map.get(name).cloned().ok_or(format!("no node named '{}'", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(Ego::new(&seeds, options.hops.unwrap_or(1))))
+}
+
+/// Interaction types in the network, less those given to `--hide`
+fn interactions(options: &Options, graph: &Graph<Node, Edge>) -> Result<draw::Interactions, String> {
+    let mut interactions = draw::Interactions::of(graph);
+    for name in &options.hide {
+        if !interactions.set(name, false) {
+            return Err(format!("no interactions of type '{}'", name));
         }
-        
+    }
+    Ok(interactions)
+}
 
-        {
-            let mut mapping = nodes.map();
-            // zip with nodelist
-            for (node, v) in mapping.iter_mut().zip(graph.node_indices()) {
-                let pos = graph[v].pos;
+/// The nodes and edges a filter, an ego network and the interaction types
+/// shown leave, if any of them hides anything
+fn shown(graph: &Graph<Node, Edge>, filter: &Filter, ego: Option<&Ego>, interactions: &draw::Interactions) -> Option<draw::Visible> {
+    if !filter.is_active() && ego.is_none() && !interactions.hides_any() {
+        return None;
+    }
+    let mut nodes = ego.map_or(vec![true; graph.node_count()], |ego| ego.apply(graph));
+    if filter.is_active() {
+        for (node, kept) in nodes.iter_mut().zip(filter.apply(graph)) {
+            *node = *node && kept;
+        }
+    }
+    let mut visible = draw::Visible::nodes(graph, nodes);
+    if interactions.hides_any() {
+        interactions.restrict(graph, &mut visible);
+    }
+    Some(visible)
+}
 
-                node.offset[0] = pos.x;
-                node.offset[1] = pos.y;
-            }
+/// The nodes and edges `--ego`, `--core`, `--min-degree` and `--hide` leave, if any was given
+fn visible(options: &Options, network: &graph::Network) -> Result<Option<draw::Visible>, String> {
+    let interactions = interactions(options, &network.graph)?;
+    Ok(shown(&network.graph, &options.filter, ego(options, network)?.as_ref(), &interactions))
+}
+
+/// The nodes and edges `extract` keeps: those picked by `--nodes`, `--find`
+/// and `--community` which the drawing filters also leave
+fn selection(options: &Options, network: &graph::Network) -> Result<draw::Visible, String> {
+    let ref graph = network.graph;
+    let mut nodes = visible(options, network)?.map_or(vec![true; graph.node_count()], |visible| visible.nodes);
+    if !options.nodes.is_empty() {
+        let mut named = vec![false; graph.node_count()];
+        for name in &options.nodes {
+            let v = network.map.get(name).ok_or(format!("no node named '{}'", name))?;
+            named[v.index()] = true;
+        }
+        for (node, named) in nodes.iter_mut().zip(named) {
+            *node = *node && named;
         }
+    }
+    for v in graph.node_indices() {
+        let found = options.find.as_ref().map_or(true, |pattern| pattern.matches(&graph[v].name));
+        let in_community = options.community.map_or(true, |c| graph[v].community == Some(c));
+        nodes[v.index()] = nodes[v.index()] && found && in_community;
+    }
+    // the edges hidden by type stay out
+    let mut selected = draw::Visible::nodes(graph, nodes);
+    let interactions = interactions(options, graph)?;
+    if interactions.hides_any() {
+        interactions.restrict(graph, &mut selected);
+    }
+    Ok(selected)
+}
+
+/// Loads the inputs and lays them out to completion
+fn load_and_layout(options: &Options, config: &Config, inputs: &[String]) -> Result<graph::Network, String> {
+    let mut network = load(inputs)?;
+    mark_communities(options.communities, options.resolution, &mut network.graph, &mut rng(options));
+    let mut layout = layout_for(options, config, &network);
+    layout::scatter(&mut network.graph, 0, &mut rng(options));
+    layout.run(&mut network.graph);
+    Ok(network)
+}
+
+fn output(options: &Options) -> &str {
+    options.output.as_ref().unwrap()
+}
+
+/// A laid out network as `camera` sees it, sized by `--centrality` and
+/// highlighting what `--find`, `--from` and `--to` pick out
+fn drawing(options: &Options, config: &Config, network: &graph::Network, camera: &draw::Camera) -> Result<draw::Drawing, String> {
+    let ref graph = network.graph;
+    let mut drawing = draw::Drawing::new(graph, &config.style, network.sources.len() > 1, camera, true);
+    if let Some(sizes) = node_sizes(options, graph) {
+        drawing.size_nodes(&sizes);
+    }
+    if let Some(highlight) = highlight(options, network)? {
+        drawing.highlight(&highlight, &config.style);
+    }
+    Ok(drawing)
+}
 
-        square = core.window.with_display(gl::base::make_square).expect("Failed making a triangle!");
-        {
-            let mut mapping = square.map();
-            // zip with nodelist
-            for vertex in mapping.iter_mut() {
-                let scalesq = scale.powf(scale);
-                vertex.position[0] /= scalesq;
-                vertex.position[1] /= scalesq;
-                vertex.position[2] /= scalesq;
+/// Writes a laid out network as a picture or a graph file, depending on the extension
+fn write(network: &graph::Network, options: &Options, config: &Config, out: &Path) -> Result<(), String> {
+    let ref graph = network.graph;
+    let extension = out.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let result = match extension.as_str() {
+        "svg" | "pdf" | "png" => {
+            let visible = visible(options, network)?;
+            // frame only what is left after filtering
+            let shown = graph.node_indices().filter(|&v| visible.as_ref().map_or(true, |visible| visible.node(v)));
+            let camera = draw::Camera::around(shown.map(|v| graph[v].pos), config.layout.width, config.layout.height, 20.);
+            let mut drawing = drawing(options, config, network, &camera)?;
+            if let Some(ref visible) = visible {
+                drawing.hide(visible);
+            }
+            match extension.as_str() {
+                "svg" => export::svg::write_file(&drawing, out),
+                "pdf" => export::pdf::write_file(&drawing, &options.page, out),
+                _ => export::png::write_file(&drawing, options.scale, out),
             }
+        },
+        _ => {
+            // graph files keep every node, sized and coloured as drawn
+            let camera = draw::Camera::fit(graph, config.layout.width, config.layout.height, 20.);
+            io::save(graph, &config.style, &drawing(options, config, network, &camera)?, out)
+        },
+    };
+    result.map_err(|e| format!("{}: {}", out.display(), e))
+}
+
+/// `layout` and `render`, which differ only in what they may write
+fn layout_and_write(options: &Options, config: &Config) -> Result<(), String> {
+    let network = load_and_layout(options, config, &options.inputs)?;
+    write(&network, options, config, Path::new(output(options)))
+}
+
+/// Lays out every input on its own, writing each in every requested format.
+/// Carries on past failures, reporting them all at the end.
+fn batch(options: &Options, config: &Config) -> Result<(), String> {
+    let dir = Path::new(options.dir.as_ref().unwrap());
+    fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let mut failed = 0;
+    for input in &options.inputs {
+        // the name without directories or extensions, `.gz` included
+        let path = Path::new(input);
+        let stem = match path.extension() {
+            Some(e) if e == "gz" => path.file_stem().map(Path::new).and_then(|p| p.file_stem()),
+            _ => path.file_stem(),
+        };
+        let stem = if input == "-" { "stdin".to_string() } else { stem.unwrap_or(path.as_os_str()).to_string_lossy().into_owned() };
+
+        let result = load_and_layout(options, config, &[input.clone()]).and_then(|network| {
+            let mut written = Vec::new();
+            for format in &options.formats {
+                let out = dir.join(format!("{}.{}", stem, format));
+                write(&network, options, config, &out)?;
+                written.push(out.display().to_string());
+            }
+            println!("{}: {} nodes, {} edges -> {}", input, network.graph.node_count(), network.graph.edge_count(), written.join(", "));
+            Ok(())
+        });
+        if let Err(message) = result {
+            eprintln!("{}", message);
+            failed += 1;
         }
+    }
+
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("{} of {} inputs failed", failed, options.inputs.len())),
+    }
+}
 
-        let mut frame = display.draw();
+fn record(options: &Options, config: &Config) -> Result<(), String> {
+    let mut network = load(&options.inputs)?;
+    mark_communities(options.communities, options.resolution, &mut network.graph, &mut rng(options));
+    let merged = network.sources.len() > 1;
+    let highlight = highlight(options, &network)?;
+    let visible = visible(options, &network)?;
+    let mut layout = layout_for(options, config, &network);
+    let ref mut graph = network.graph;
+    layout::scatter(graph, 0, &mut rng(options));
+
+    let positions = |graph: &Graph<Node, Edge>| graph.node_indices().map(|v| graph[v].pos).collect::<Vec<_>>();
+    let mut snapshots = vec![positions(graph)];
+    while !layout.is_done() {
+        layout.step(graph);
+        snapshots.push(positions(graph));
+    }
 
-        frame.clear_color(0.1, 0.1, 0.1, 0.0);
-        frame.draw((&nodes, &zero), &edges.0, &program, &edge_uniforms, &lineparams).unwrap();
-        frame.draw((&nodes, &zero), &edges.1, &program, &edge_uniforms2, &lineparams).unwrap();
-        frame.draw((&square, nodes.per_instance().unwrap()), &indices, &program, &node_uniforms, &Default::default()).unwrap();
+    // one camera for the whole animation, so the view holds still
+    let camera = draw::Camera::around(snapshots.iter().flat_map(|s| s.iter().cloned()), config.layout.width, config.layout.height, 20.);
+    let sizes = node_sizes(options, graph);
+    let out = output(options);
+    let mut recording = export::record::Recording::new(out, options.fps, options.scale);
+    for snapshot in snapshots {
+        for (v, pos) in graph.node_indices().zip(snapshot) {
+            graph[v].pos = pos;
+        }
+        let mut drawing = draw::Drawing::new(graph, &config.style, merged, &camera, true);
+        if let Some(ref sizes) = sizes {
+            drawing.size_nodes(sizes);
+        }
+        if let Some(ref highlight) = highlight {
+            drawing.highlight(highlight, &config.style);
+        }
+        if let Some(ref visible) = visible {
+            drawing.hide(visible);
+        }
+        recording.frame(&drawing).map_err(|e| format!("{}: {}", out, e))?;
+    }
+    println!("Recorded {} frames to {}", recording.frames(), out);
+    Ok(())
+}
 
-        iteration += 1;
+fn convert(options: &Options, config: &Config) -> Result<(), String> {
+    let network = load(&options.inputs)?;
+    let out = output(options);
+    let ref graph = network.graph;
+    let camera = draw::Camera::fit(graph, config.layout.width, config.layout.height, 20.);
+    let drawing = draw::Drawing::new(graph, &config.style, false, &camera, false);
+    io::save(graph, &config.style, &drawing, out).map_err(|e| format!("{}: {}", out, e))
+}
+
+fn stats(options: &Options) -> Result<(), String> {
+    let network = load(&options.inputs)?;
+    let statistics = analysis::Statistics::of(&network.graph, options.samples, &mut rng(options));
+    for line in statistics.lines() {
+        println!("{}", line);
+    }
+    println!("positions: {}", if network.is_placed() { "complete" } else { "none or partial" });
+    if let Some(method) = options.communities {
+        let found = analysis::community::detect(&network.graph, method, options.resolution, &mut rng(options));
+        let count = found.iter().map(|&c| c + 1).max().unwrap_or(0);
+        let mut sizes = vec![0; count];
+        for &c in &found {
+            sizes[c] += 1;
+        }
+        let mut listed = sizes.iter().take(10).map(|s| s.to_string()).collect::<Vec<_>>();
+        if count > 10 {
+            listed.push("...".to_string());
+        }
+        println!("communities by {}: {} (sizes {})", method.name(), count, listed.join(", "));
+        println!("modularity: {:.4}", analysis::community::modularity(&network.graph, &found, options.resolution));
+    }
+    if let Some(centrality) = options.centrality {
+        let values = centrality.of(&network.graph, options.samples, &mut rng(options));
+        println!("most central by {}:", centrality.name());
+        for v in analysis::centrality::top(&values, 10) {
+            println!("  {}: {:.4}", network.graph[NodeIndex::new(v)].name, values[v]);
+        }
+    }
+    Ok(())
+}
 
-        frame.finish().unwrap();
+/// Saves the selected part of the network, laid out as a whole
+fn extract(options: &Options, config: &Config) -> Result<(), String> {
+    let network = load_and_layout(options, config, &options.inputs)?;
+    let selected = selection(options, &network)?;
+    let part = graph::part(&network.graph, &selected.nodes, &selected.edges);
+    let camera = draw::Camera::fit(&network.graph, config.layout.width, config.layout.height, 20.);
+    let mut drawing = draw::Drawing::new(&network.graph, &config.style, network.sources.len() > 1, &camera, false);
+    drawing.hide(&selected);
+    let out = output(options);
+    io::save(&part, &config.style, &drawing, out).map_err(|e| format!("{}: {}", out, e))?;
+    println!("Extracted {} of {} nodes and {} of {} edges to {}",
+             part.node_count(), network.graph.node_count(), part.edge_count(), network.graph.edge_count(), out);
+    Ok(())
+}
 
-        if shutdown {
-            break;
+/// Prints each path as its nodes joined by their interactions
+fn path(options: &Options) -> Result<(), String> {
+    let network = load(&options.inputs)?;
+    let found = find_paths(options, &network)?;
+    if found.is_empty() {
+        return Err(format!("no path between {} and {}", options.from.as_ref().unwrap(), options.to.as_ref().unwrap()));
+    }
+    let ref graph = network.graph;
+    for path in found {
+        let mut line = graph[path.nodes[0]].name.clone();
+        for (e, v) in path.edges.iter().zip(&path.nodes[1..]) {
+            line.push_str(&format!(" -{}- {}", graph[*e].interaction, graph[*v].name));
+        }
+        match options.weights {
+            Some(_) => println!("{}  (cost {})", line, path.cost),
+            None => println!("{}  ({} hops)", line, path.edges.len()),
         }
     }
+    Ok(())
+}
 
+fn main() {
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("life: {}", message);
+            eprintln!("Run `life --help` for usage.");
+            process::exit(2);
+        },
+    };
+
+    if options.help {
+        println!("{}", cli::help(options.command));
+        return;
+    }
+
+    let result = settings(&options).and_then(|config| match options.command.unwrap() {
+        Command::View => view(&options, &config),
+        Command::Layout | Command::Render => layout_and_write(&options, &config),
+        Command::Batch => batch(&options, &config),
+        Command::Record => record(&options, &config),
+        Command::Convert => convert(&options, &config),
+        Command::Stats => stats(&options),
+        Command::Path => path(&options),
+        Command::Extract => extract(&options, &config),
+        Command::Defaults => {
+            println!("{}", config.to_json());
+            Ok(())
+        },
+    });
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,123 @@
+//! Colours shared by the window and anything else that draws the network
+
+use petgraph::Graph;
+use graph::{Node, Edge};
+
+pub type Colour = [f32; 4];
+
+pub const BACKGROUND: Colour = [0.1, 0.1, 0.1, 1.0];
+pub const NODE: Colour = [0.0, 0.6, 0.0, 1.0];
+pub const LABEL: Colour = [0.9, 0.9, 0.9, 1.0];
+pub const EDGE: Colour = [0.0, 0.0, 0.0, 0.7];
+pub const OVERLAY: Colour = [1.0, 0.0, 0.0, 0.7];
+/// Edges found in more than one merged input
+pub const SHARED: Colour = [0.9, 0.9, 0.9, 0.7];
+/// Nodes and edges picked out, eg. along a path
+pub const HIGHLIGHT: Colour = [1.0, 0.8, 0.0, 1.0];
+/// Opacity kept by everything else while something is highlighted
+pub const DIMMED: f32 = 0.2;
+
+/// Tableau 10, distinguishable on the dark background
+const PALETTE: [[f32; 3]; 10] = [
+    [0.12, 0.47, 0.71],
+    [1.00, 0.50, 0.05],
+    [0.17, 0.63, 0.17],
+    [0.84, 0.15, 0.16],
+    [0.58, 0.40, 0.74],
+    [0.55, 0.34, 0.29],
+    [0.89, 0.47, 0.76],
+    [0.50, 0.50, 0.50],
+    [0.74, 0.74, 0.13],
+    [0.09, 0.75, 0.81],
+];
+
+/// Colour for the `i`th item of some category, repeating after ten
+pub fn categorical(i: usize, alpha: f32) -> Colour {
+    let [r, g, b] = PALETTE[i % PALETTE.len()];
+    [r, g, b, alpha]
+}
+
+/// Stops of a sequential ramp, dark blue through green to yellow like viridis,
+/// lightened at the low end to stay visible on the dark background
+const RAMP: [[f32; 3]; 5] = [
+    [0.23, 0.32, 0.55],
+    [0.16, 0.47, 0.56],
+    [0.13, 0.63, 0.53],
+    [0.48, 0.82, 0.32],
+    [0.99, 0.91, 0.14],
+];
+
+/// Colour for a value from 0 to 1 on a sequential scale
+pub fn ramp(t: f32, alpha: f32) -> Colour {
+    let t = t.max(0.).min(1.) * (RAMP.len() - 1) as f32;
+    let i = (t as usize).min(RAMP.len() - 2);
+    let f = t - i as f32;
+    let (a, b) = (RAMP[i], RAMP[i + 1]);
+    [a[0] + (b[0] - a[0]) * f, a[1] + (b[1] - a[1]) * f, a[2] + (b[2] - a[2]) * f, alpha]
+}
+
+/// The colours everything is drawn in, by default the constants above
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Style {
+    pub background: Colour,
+    pub node: Colour,
+    pub label: Colour,
+    pub edge: Colour,
+    pub overlay: Colour,
+    pub shared: Colour,
+    pub highlight: Colour,
+    pub dimmed: f32,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            background: BACKGROUND,
+            node: NODE,
+            label: LABEL,
+            edge: EDGE,
+            overlay: OVERLAY,
+            shared: SHARED,
+            highlight: HIGHLIGHT,
+            dimmed: DIMMED,
+        }
+    }
+}
+
+impl Style {
+    /// Edges of a merged network are coloured by the input they came from,
+    /// otherwise `OVERLAY` edges are picked out
+    pub fn edge_colour(&self, edge: &Edge, merged: bool) -> Colour {
+        if merged {
+            match edge.sources.len() {
+                1 => categorical(edge.sources[0], self.edge[3]),
+                _ => self.shared,
+            }
+        } else if edge.interaction == "OVERLAY" {
+            self.overlay
+        } else {
+            self.edge
+        }
+    }
+
+    /// `colour` faded into the background, for what isn't highlighted
+    pub fn dim(&self, colour: Colour) -> Colour {
+        [colour[0], colour[1], colour[2], colour[3] * self.dimmed]
+    }
+}
+
+/// Interaction types in order of first appearance, each with its own colour,
+/// except `OVERLAY` which is picked out as in `Style::edge_colour`
+pub fn interaction_colours(graph: &Graph<Node, Edge>, style: &Style) -> Vec<(String, Colour)> {
+    let mut types: Vec<String> = Vec::new();
+    for edge in graph.raw_edges() {
+        if !types.contains(&edge.weight.interaction) {
+            types.push(edge.weight.interaction.clone());
+        }
+    }
+    types.into_iter().enumerate().map(|(i, t)| {
+        let colour = if t == "OVERLAY" { style.overlay } else { categorical(i, 1.0) };
+        (t, colour)
+    }).collect()
+}
@@ -0,0 +1,1022 @@
+//! The window, laying out the network while showing it
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use cgmath::{self, Matrix4, Vector2, Vector3};
+use petgraph::Graph;
+use petgraph::graph::{NodeIndex, EdgeIndex};
+use rand::Rng;
+use glium::{self, Surface};
+use glium::glutin::VirtualKeyCode;
+use conrod::{self, widget, Colorable, Labelable, Positionable, Sizeable, Widget};
+use life::*;
+use life::analysis::centrality::{self, Centrality};
+use life::analysis::community::Method;
+use life::analysis::cores::Filter;
+use life::analysis::ego::Ego;
+use life::config::{Config, Keys};
+use life::core::Core;
+use life::core::window::Window;
+use life::gl::cgtraits::AsUniform;
+use life::graph::{Node, Edge};
+use life::graph::search::Pattern;
+use life::io::stream::SifStream;
+use life::support::{conrod_backend, winit};
+use cli::Options;
+use super::{load, rng, layout_for, mark_communities, find_paths, highlight_paths, path_highlight, ego, shown, interactions};
+use super::{highlight_found, join_highlights};
+
+/// How near the pointer a node must be drawn to be picked, in pixels
+const PICK_DISTANCE: f32 = 8.;
+/// Text over the network: its size, the spacing of lines and the margin, in pixels
+const TEXT_SIZE: f32 = 16.;
+const LINE_HEIGHT: f32 = 20.;
+const TEXT_MARGIN: f32 = 10.;
+/// Least zoom when centring on a node found, as the mouse wheel sets it
+const FOUND_SCALE: f32 = 1.5;
+/// The filter sliders at the top right: their size and spacing, in pixels
+const SLIDER_WIDTH: f64 = 220.;
+const SLIDER_HEIGHT: f64 = 24.;
+const SLIDER_GAP: f64 = 6.;
+const SLIDER_TEXT: conrod::FontSize = 12;
+/// Side of each interaction type's checkbox, in pixels
+const CHECKBOX_SIZE: f64 = 14.;
+
+/// How long a stream must stop growing before communities, centralities and
+/// statistics are worked out again, in milliseconds
+const STREAM_SETTLE: u64 = 500;
+
+/// How nodes and edges look, besides where they are
+struct Appearance {
+    /// Centralities scaled from 0 to 1, sizing and colouring the nodes
+    sizes: Option<Vec<f32>>,
+    /// Paths picked out
+    highlight: Option<draw::Highlight>,
+    /// What the filters leave, everything if none
+    visible: Option<draw::Visible>,
+}
+
+impl Appearance {
+    /// Sizes and colours every node and edge of a drawing as in the window
+    fn colour(&self, drawing: &mut draw::Drawing, style: &style::Style) {
+        if let Some(ref sizes) = self.sizes {
+            drawing.size_nodes(sizes);
+        }
+        if let Some(ref highlight) = self.highlight {
+            drawing.highlight(highlight, style);
+        }
+    }
+
+    /// Makes an exported picture look like the window
+    fn apply(&self, drawing: &mut draw::Drawing, style: &style::Style) {
+        self.colour(drawing, style);
+        if let Some(ref visible) = self.visible {
+            drawing.hide(visible);
+        }
+    }
+
+    fn shows_node(&self, v: NodeIndex) -> bool {
+        self.visible.as_ref().map_or(true, |visible| visible.node(v))
+    }
+
+    fn shows_edge(&self, e: EdgeIndex) -> bool {
+        self.visible.as_ref().map_or(true, |visible| visible.edge(e))
+    }
+}
+
+fn build_nodes(display: &glium::Display, graph: &Graph<Node, Edge>, style: &style::Style, appearance: &Appearance) -> glium::VertexBuffer<gl::base::Instance> {
+    let data = graph.node_indices().filter(|&v| appearance.shows_node(v)).map(|index| {
+        let pos = graph[index].pos;
+        let (scale, mut rgba) = match appearance.sizes {
+            Some(ref sizes) => (draw::node_scale(sizes[index.index()]), style::ramp(sizes[index.index()], style.node[3])),
+            None => (1., match graph[index].community {
+                Some(c) => style::categorical(c, style.node[3]),
+                None => style.node,
+            }),
+        };
+        if let Some(ref highlight) = appearance.highlight {
+            rgba = highlight.node_colour(index, rgba, style);
+        }
+        gl::base::Instance {
+            offset: [pos.x, pos.y, 0.0],
+            scale: scale,
+            rgba: rgba,
+        }
+    }).collect::<Vec<_>>();
+    glium::vertex::VertexBuffer::dynamic(display, &data).unwrap()
+}
+
+/// Edges grouped by colour, so each group is one draw call, with the line
+/// segments tracing each group
+struct EdgeGroups {
+    groups: Vec<(style::Colour, Vec<EdgeIndex>)>,
+    segments: Vec<glium::VertexBuffer<gl::base::Offset>>,
+    /// The loop radius the segments were traced with, none before they are
+    traced: Option<f32>,
+}
+
+impl EdgeGroups {
+    /// Traces the segments again, after the nodes move or the loops change size
+    fn trace(&mut self, display: &glium::Display, graph: &Graph<Node, Edge>, multiplicities: &[geometry::Multiplicity], loop_radius: f32) {
+        self.segments = self.groups.iter().map(|&(_, ref group)| build_segments(display, graph, group, multiplicities, loop_radius)).collect();
+        self.traced = Some(loop_radius);
+    }
+}
+
+fn group_edges(graph: &Graph<Node, Edge>, style: &style::Style, merged: bool, appearance: &Appearance) -> EdgeGroups {
+    let mut groups: Vec<(style::Colour, Vec<EdgeIndex>)> = Vec::new();
+    graph.edge_indices().filter(|&e| appearance.shows_edge(e)).for_each(|index| {
+        let mut colour = style.edge_colour(&graph[index], merged);
+        if let Some(ref highlight) = appearance.highlight {
+            colour = highlight.edge_colour(index, colour, style);
+        }
+        match groups.iter().position(|g| g.0 == colour) {
+            Some(i) => groups[i].1.push(index),
+            None => groups.push((colour, vec![index])),
+        }
+    });
+    EdgeGroups {
+        groups: groups,
+        segments: Vec::new(),
+        traced: None,
+    }
+}
+
+/// What a key does in the window
+#[derive(Copy, Clone)]
+enum Action {
+    /// Save the layout with this extension
+    Save(&'static str),
+    /// Save the nodes and edges shown, with their layout
+    SaveShown,
+    /// Export the view with this extension
+    Export(&'static str),
+    Record,
+    /// Show or hide the statistics panel
+    Statistics,
+    /// Size nodes by the next centrality, or none after the last
+    Centrality,
+    /// Colour nodes by community, or stop
+    Communities,
+    /// Show a deeper or shallower k-core
+    Core(isize),
+    /// Raise or lower the least degree shown
+    Degree(isize),
+    /// Show one more hop around the node under the pointer
+    Expand,
+    /// Show one hop less around it
+    Collapse,
+    /// Show or hide the list of interaction types
+    Interactions,
+    /// Show or hide edges of the interaction type listed in this place
+    InteractionType(usize),
+    /// Type a search
+    Find,
+    /// Centre on the next or previous node found
+    Found(isize),
+}
+
+/// A key as named in the configuration: a letter, a digit, `F1` to `F12`,
+/// `Space`, `Tab`, `Escape`, `Return` or an arrow, `Up`, `Down`, `Left` or `Right`
+fn key_code(name: &str) -> Option<VirtualKeyCode> {
+    use glium::glutin::VirtualKeyCode::*;
+    const LETTERS: [VirtualKeyCode; 26] = [A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z];
+    const DIGITS: [VirtualKeyCode; 10] = [Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9];
+    const FUNCTIONS: [VirtualKeyCode; 12] = [F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12];
+
+    let upper = name.to_uppercase();
+    let mut chars = upper.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c @ 'A'..='Z'), None) => return Some(LETTERS[c as usize - 'A' as usize]),
+        (Some(c @ '0'..='9'), None) => return Some(DIGITS[c as usize - '0' as usize]),
+        _ => (),
+    }
+    match upper.as_str() {
+        "SPACE" => Some(Space),
+        "UP" => Some(Up),
+        "DOWN" => Some(Down),
+        "LEFT" => Some(Left),
+        "RIGHT" => Some(Right),
+        "TAB" => Some(Tab),
+        "ESCAPE" => Some(Escape),
+        "RETURN" | "ENTER" => Some(Return),
+        _ if upper.starts_with('F') => upper[1..].parse::<usize>().ok()
+            .and_then(|n| if n >= 1 && n <= 12 { Some(FUNCTIONS[n - 1]) } else { None }),
+        _ => None,
+    }
+}
+
+/// The configured keys, each bound to one action
+fn bindings(keys: &Keys) -> Result<Vec<(VirtualKeyCode, Action)>, String> {
+    let named = [
+        ("save_cyjs", &keys.save_cyjs, Action::Save("cyjs")),
+        ("save_gexf", &keys.save_gexf, Action::Save("gexf")),
+        ("save_graphml", &keys.save_graphml, Action::Save("graphml")),
+        ("save_dot", &keys.save_dot, Action::Save("dot")),
+        ("save_tikz", &keys.save_tikz, Action::Save("tex")),
+        ("save_shown", &keys.save_shown, Action::SaveShown),
+        ("export_svg", &keys.export_svg, Action::Export("svg")),
+        ("export_pdf", &keys.export_pdf, Action::Export("pdf")),
+        ("export_png", &keys.export_png, Action::Export("png")),
+        ("record", &keys.record, Action::Record),
+        ("statistics", &keys.statistics, Action::Statistics),
+        ("centrality", &keys.centrality, Action::Centrality),
+        ("communities", &keys.communities, Action::Communities),
+        ("core_up", &keys.core_up, Action::Core(1)),
+        ("core_down", &keys.core_down, Action::Core(-1)),
+        ("degree_up", &keys.degree_up, Action::Degree(1)),
+        ("degree_down", &keys.degree_down, Action::Degree(-1)),
+        ("expand", &keys.expand, Action::Expand),
+        ("collapse", &keys.collapse, Action::Collapse),
+        ("interactions", &keys.interactions, Action::Interactions),
+        ("find", &keys.find, Action::Find),
+        ("next_found", &keys.next_found, Action::Found(1)),
+        ("previous_found", &keys.previous_found, Action::Found(-1)),
+    ];
+    let types = keys.interaction_types.iter().enumerate().map(|(i, key)| ("interaction_types", key, Action::InteractionType(i)));
+    let mut bindings: Vec<(VirtualKeyCode, Action)> = Vec::new();
+    let mut names: Vec<&str> = Vec::new();
+    for (name, key, action) in named.iter().cloned().chain(types) {
+        let code = key_code(key).ok_or(format!("keys.{}: unknown key '{}'", name, key))?;
+        if let Some(i) = bindings.iter().position(|b| b.0 == code) {
+            return Err(format!("keys.{}: '{}' is already bound to {}", name, key, names[i]));
+        }
+        bindings.push((code, action));
+        names.push(name);
+    }
+    Ok(bindings)
+}
+
+/// Centralities scaled from 0 to 1, for sizing nodes
+fn measure<R: Rng>(centrality: Option<Centrality>, graph: &Graph<Node, Edge>, samples: usize, rng: &mut R) -> Option<Vec<f32>> {
+    centrality.map(|c| centrality::normalise(&c.of(graph, samples, rng)))
+}
+
+/// A file named after the input with another extension, unless that is the
+/// input itself, which the window never overwrites
+fn beside(filename: &str, extension: &str) -> Result<PathBuf, String> {
+    let out = Path::new(filename).with_extension(extension);
+    match (fs::canonicalize(&out), fs::canonicalize(filename)) {
+        (Ok(ref a), Ok(ref b)) if a == b => Err(format!("Not overwriting the input {}", out.display())),
+        _ => Ok(out),
+    }
+}
+
+// the controls drawn with conrod over the network
+widget_ids! {
+    struct Ids {
+        core,
+        degree,
+        types[],
+        type_names[],
+    }
+}
+
+fn conrod_colour(colour: style::Colour) -> conrod::Color {
+    conrod::color::rgba(colour[0], colour[1], colour[2], colour[3])
+}
+
+/// Sliders for the k-core and the least degree shown, at the top right,
+/// returning the filter they were dragged to if they were. `limits` is how
+/// far they reach.
+fn filter_sliders(ui: &mut conrod::UiCell, ids: &Ids, filter: Filter, limits: Filter, style: &style::Style) -> Option<Filter> {
+    let label = conrod_colour(style.label);
+    let mut changed = filter;
+    // a range of at least one, so a network without edges still gets sliders
+    let core = widget::Slider::new(filter.core as f32, 0., limits.core.max(1) as f32)
+        .label(&format!("k-core {}", filter.core))
+        .label_font_size(SLIDER_TEXT)
+        .label_color(label)
+        .w_h(SLIDER_WIDTH, SLIDER_HEIGHT)
+        .top_right_with_margin_on(ui.window, TEXT_MARGIN as f64)
+        .set(ids.core, ui);
+    if let Some(k) = core {
+        changed.core = k.round() as usize;
+    }
+    let degree = widget::Slider::new(filter.degree as f32, 0., limits.degree.max(1) as f32)
+        .label(&format!("least degree {}", filter.degree))
+        .label_font_size(SLIDER_TEXT)
+        .label_color(label)
+        .w_h(SLIDER_WIDTH, SLIDER_HEIGHT)
+        .down(SLIDER_GAP)
+        .set(ids.degree, ui);
+    if let Some(d) = degree {
+        changed.degree = d.round() as usize;
+    }
+    if changed != filter { Some(changed) } else { None }
+}
+
+/// A checkbox for each interaction type under the sliders, beside its key,
+/// name and number of edges, returning the one clicked if any
+fn type_checkboxes(ui: &mut conrod::UiCell, ids: &mut Ids, interactions: &draw::Interactions, keys: &Keys, style: &style::Style) -> Option<usize> {
+    let label = conrod_colour(style.label);
+    ids.types.resize(interactions.types.len(), &mut ui.widget_id_generator());
+    ids.type_names.resize(interactions.types.len(), &mut ui.widget_id_generator());
+    let mut clicked = None;
+    for (i, &(ref name, count, shown)) in interactions.types.iter().enumerate() {
+        // filled when shown, dark when hidden
+        let checkbox = widget::Toggle::new(shown).color(label).w_h(CHECKBOX_SIZE, CHECKBOX_SIZE);
+        let checkbox = match i {
+            0 => checkbox.down_from(ids.degree, SLIDER_GAP * 2.).align_left_of(ids.degree),
+            _ => checkbox.down_from(ids.types[i - 1], SLIDER_GAP).align_left_of(ids.types[i - 1]),
+        };
+        if checkbox.set(ids.types[i], ui).next().is_some() {
+            clicked = Some(i);
+        }
+        let key = keys.interaction_types.get(i).map_or("", |k| k.as_str());
+        widget::Text::new(&format!("{} {} ({})", key, name, count))
+            .font_size(SLIDER_TEXT)
+            .color(label)
+            .right_from(ids.types[i], SLIDER_GAP)
+            .align_middle_y_of(ids.types[i])
+            .set(ids.type_names[i], ui);
+    }
+    clicked
+}
+
+/// Whether the pointer is over a control, where a press isn't a drag.
+/// `checkboxes` tells whether the interaction types are listed.
+fn over_controls(ui: &conrod::Ui, ids: &Ids, checkboxes: bool) -> bool {
+    let xy = ui.global_input().current.mouse.xy;
+    let types = if checkboxes { &ids.types[..] } else { &[] };
+    [ids.core, ids.degree].iter().chain(types).any(|&id| ui.rect_of(id).map_or(false, |rect| rect.is_over(xy)))
+}
+
+/// Line segments tracing each edge's current shape
+fn build_segments(display: &glium::Display, graph: &Graph<Node, Edge>, edges: &[EdgeIndex],
+                  multiplicities: &[geometry::Multiplicity], loop_radius: f32) -> glium::VertexBuffer<gl::base::Offset> {
+    let mut data = Vec::new();
+    for &e in edges {
+        let path = geometry::edge_path(graph, e, multiplicities[e.index()], loop_radius);
+        for pair in path.windows(2) {
+            data.push(gl::base::Offset { offset: [pair[0].x, pair[0].y, 0.0] });
+            data.push(gl::base::Offset { offset: [pair[1].x, pair[1].y, 0.0] });
+        }
+    }
+    glium::VertexBuffer::new(display, &data).unwrap()
+}
+
+pub fn view(options: &Options, config: &Config) -> Result<(), String> {
+    let bindings = bindings(&config.keys)?;
+
+    // `life view -` follows SIF from stdin as it arrives
+    let mut stream = None;
+    // when the stream last grew, until the whole graph is measured again
+    let mut grew: Option<Instant> = None;
+    let mut mapped_graph = if options.inputs.len() == 1 && options.inputs[0] == "-" {
+        stream = Some(SifStream::stdin());
+        graph::Network::new()
+    } else {
+        load(&options.inputs)?
+    };
+    let filename = if options.inputs[0] == "-" { "stdin" } else { options.inputs[0].as_str() };
+    let merged = mapped_graph.sources.len() > 1;
+
+    let mut core = Core::with_window(Window::with_size(config.window.width, config.window.height, &config.window.title));
+
+    // let life core handle the mainloop
+
+    let mut scale: f32 = 1.0;
+    let mut translation = Vector3::new(0., 0., 0.,);
+    let mut movement = Vector3::new(0., 0., 0.,);
+
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+
+    let mut rng = rng(options);
+    // keep positions loaded from the file
+    layout::scatter(&mut mapped_graph.graph, 0, &mut rng);
+
+    // communities colour the nodes, from --communities or the key
+    let mut communities = options.communities;
+    let mut toggle_communities = false;
+    mark_communities(communities, options.resolution, &mut mapped_graph.graph, &mut rng);
+
+    // --core and --min-degree, then stepped by keys
+    let mut filter = options.filter;
+    let mut step_filter = None;
+    // the --ego neighbourhood, grown and shrunk around nodes by keys; a stream doesn't have the seeds yet
+    let mut neighbourhood = if stream.is_some() { None } else { ego(options, &mapped_graph)? };
+    let mut change_neighbourhood = None;
+    // interaction types shown, less --hide; a stream's are hidden as they arrive
+    let mut types = if stream.is_some() { draw::Interactions::default() } else { interactions(options, &mapped_graph.graph)? };
+    let mut toggle_type = None;
+    // the last search, from --find or typed, and the nodes it found; a stream's are found as they arrive
+    let mut searched = options.find.clone();
+    let mut found = match searched {
+        Some(ref pattern) if stream.is_none() => pattern.find(&mapped_graph.graph),
+        _ => Vec::new(),
+    };
+    // which of them the view is centred on
+    let mut current: Option<usize> = None;
+    let mut step_found = None;
+    // the search being typed
+    let mut query: Option<String> = None;
+    let mut open_query = false;
+    let mut run_query = false;
+    // a stream doesn't have the path's ends yet
+    let mut path_nodes = if stream.is_some() { None } else { path_highlight(options, &mapped_graph)? };
+
+    let mut square;
+    // nodes are sized by a centrality once one is chosen, with --centrality or the key
+    let mut centrality = options.centrality;
+    let mut appearance = Appearance {
+        sizes: measure(centrality, &mapped_graph.graph, options.samples, &mut rng),
+        highlight: join_highlights(path_nodes.clone(), highlight_found(&found)),
+        visible: shown(&mapped_graph.graph, &filter, neighbourhood.as_ref(), &types),
+    };
+    let mut cycle_centrality = false;
+    let mut nodes = build_nodes(&core.window.clone_display(), &mapped_graph.graph, &config.style, &appearance);
+
+    let line_program = core.window.with_display(gl::base::compile_line_program).unwrap();
+    let node_program = core.window.with_display(gl::base::compile_node_program).unwrap();
+
+    let mut edges = group_edges(&mapped_graph.graph, &config.style, merged, &appearance);
+    let mut multiplicities = geometry::multiplicities(&mapped_graph.graph);
+    let lines = glium::index::NoIndices(glium::index::PrimitiveType::LinesList);
+
+    let multi = geometry::MultiEdges::of(&mapped_graph.graph);
+    if multi.self_loops > 0 || multi.parallel_pairs > 0 {
+        println!("{} self-loops, {} node pairs with parallel edges (at most {})", multi.self_loops, multi.parallel_pairs, multi.max_parallel);
+    }
+
+
+    let lineparams = glium::DrawParameters {
+        blend: glium::Blend::alpha_blending(),
+        //line_width: Some(2.),
+        //smooth: Some(glium::draw_parameters::Smooth::Nicest),
+        polygon_mode: glium::draw_parameters::PolygonMode::Line,
+        .. Default::default()
+    };
+
+
+    // force-directed algorithm
+    let (mut w, mut h) = (config.window.width as f32, config.window.height as f32);
+    let mut layout = layout_for(options, config, &mapped_graph);
+
+    let mut shutdown = false;
+    let display = core.window.clone_display();
+
+    let mut projection: Matrix4<f32> = Matrix4::from(cgmath::Ortho {
+        left: -w/2.,
+        right: w/2.,
+        bottom: -h/2.,
+        top: h/2.,
+        near: -1.0,
+        far: 1.0 });
+
+    let mut mousedown = false;
+    // extension of the layout file to save this frame
+    let mut save = None;
+    // whether to save just what is shown, filtered, found around or picked out by type
+    let mut save_shown = false;
+    // extension of the picture to export this frame
+    let mut export = None;
+    // the record key starts and stops recording the view as a GIF, one frame per layout iteration
+    let mut toggle_recording = false;
+    let mut recording: Option<export::record::Recording> = None;
+    // lines of the statistics panel while it's shown, measured when opened
+    let mut toggle_statistics = false;
+    let mut statistics: Option<Vec<String>> = None;
+    // the list of interaction types, each with a checkbox
+    let mut toggle_interactions = false;
+    let mut show_interactions = false;
+    let text = gl::base::init_text(display.clone(), 24).unwrap();
+    // sliders for the filters, drawn with conrod over the network
+    let mut ui = conrod::UiBuilder::new([w as f64, h as f64]).build();
+    ui.fonts.insert(conrod::text::FontCollection::from_bytes(font::BYTES).into_font().unwrap());
+    let mut ids = Ids::new(ui.widget_id_generator());
+    let mut renderer = conrod_backend::Renderer::new(&display).map_err(|e| format!("Failed setting up the controls: {}", e))?;
+    let image_map = conrod::image::Map::<glium::texture::Texture2d>::new();
+    // how far the sliders reach, measured again when a stream settles
+    let mut limits = Filter::limits(&mapped_graph.graph);
+    let (mut m_x, mut m_y) = (0.0, 0.0);
+    loop {
+
+        {
+            use glium::glutin::{DeviceEvent, WindowEvent, Event, ElementState, MouseButton, MouseScrollDelta};
+
+            core.window.events_loop.poll_events(|e| {
+                if let Some(input) = winit::convert_event(e.clone(), &display) {
+                    ui.handle_event(input);
+                }
+                match e {
+                    Event::DeviceEvent { event, .. } => {
+                        match event {
+
+                            DeviceEvent::MouseWheel { delta } => {
+                                let (mut _x, mut y);
+                                match delta {
+                                    MouseScrollDelta::LineDelta(lx, ly) => {_x = lx; y = ly},
+                                    MouseScrollDelta::PixelDelta(lx, ly) => {_x = lx; y = ly},
+                                }
+                                scale += y / 100.;
+                                if scale < 1. {
+                                    scale = 1.;
+                                }
+                            },
+
+                            _ => (),
+                        }
+                    },
+
+                    Event::WindowEvent { event, .. } => match event {
+
+                        WindowEvent::CursorMoved { position, .. } => {
+                            if mousedown {
+                                movement.x += m_x - position.0 as f32;
+                                movement.y += m_y - position.1 as f32;
+                            }
+                            // this will always be set, and up to date
+                            m_x = position.0 as f32;
+                            m_y = position.1 as f32;
+                        },
+
+                        WindowEvent::Resized(x, y) => {
+
+                            mousedown = false;
+
+                            w = x as f32;
+                            h = y as f32;
+
+                            projection = Matrix4::from(cgmath::Ortho {
+                                left: -w/2.,
+                                right: w/2.,
+                                bottom: -h/2.,
+                                top: h/2.,
+                                near: -1.0,
+                                far: 1.0 });
+                        },
+
+                        WindowEvent::Closed => {
+                            shutdown = true;
+                        },
+
+                        WindowEvent::KeyboardInput { input, .. } => {
+                            if input.state == ElementState::Pressed && query.is_some() {
+                                // keys edit the search while it's typed
+                                match input.virtual_keycode {
+                                    Some(VirtualKeyCode::Return) => run_query = true,
+                                    Some(VirtualKeyCode::Escape) => query = None,
+                                    Some(VirtualKeyCode::Back) => if let Some(ref mut text) = query {
+                                        text.pop();
+                                    },
+                                    _ => (),
+                                }
+                            } else if input.state == ElementState::Pressed {
+                                let action = bindings.iter().find(|b| Some(b.0) == input.virtual_keycode).map(|b| b.1);
+                                match action {
+                                    Some(Action::Save(extension)) => save = Some(extension),
+                                    Some(Action::SaveShown) => save_shown = true,
+                                    Some(Action::Export(extension)) => export = Some(extension),
+                                    Some(Action::Record) => toggle_recording = true,
+                                    Some(Action::Statistics) => toggle_statistics = true,
+                                    Some(Action::Centrality) => cycle_centrality = true,
+                                    Some(Action::Communities) => toggle_communities = true,
+                                    Some(step @ Action::Core(_)) | Some(step @ Action::Degree(_)) => step_filter = Some(step),
+                                    Some(change @ Action::Expand) | Some(change @ Action::Collapse) => change_neighbourhood = Some(change),
+                                    Some(Action::Interactions) => toggle_interactions = true,
+                                    Some(Action::InteractionType(i)) => toggle_type = Some(i),
+                                    Some(Action::Find) => open_query = true,
+                                    Some(Action::Found(step)) => step_found = Some(step),
+                                    None => (),
+                                }
+                            }
+                        },
+
+                        WindowEvent::ReceivedCharacter(c) => {
+                            if let Some(ref mut text) = query {
+                                if !c.is_control() {
+                                    text.push(c);
+                                }
+                            }
+                        },
+
+                        WindowEvent::MouseInput { button, state, .. } => {
+                            match button {
+                                MouseButton::Left => {
+                                    match state {
+                                        ElementState::Pressed => {
+                                            if m_x > 0.0 && m_y > 0.0 && !over_controls(&ui, &ids, show_interactions) {
+                                                mousedown = true;
+                                            }
+                                        },
+                                        ElementState::Released => {
+                                            mousedown = false;
+                                        },
+                                    }
+                                },
+                                _ => {},
+                            }
+                        },
+
+                        _ => (),
+
+                    },
+
+                    _ => (),
+
+                }
+            });
+
+        }
+
+        // only now, so the key opening the search isn't typed into it
+        if open_query {
+            open_query = false;
+            query = Some(String::new());
+        }
+
+        if let Some(ref mut stream) = stream {
+            let from = mapped_graph.graph.node_count();
+            let grown = match stream.poll(&mut mapped_graph) {
+                Ok(grown) => grown,
+                Err(e) => {
+                    eprintln!("stdin: {}", e);
+                    true
+                },
+            };
+            if grown {
+                layout::scatter(&mut mapped_graph.graph, from, &mut rng);
+                path_nodes = find_paths(options, &mapped_graph).ok().and_then(|found| highlight_paths(&found));
+                if let Some(ref pattern) = searched {
+                    found = pattern.find(&mapped_graph.graph);
+                }
+                appearance.highlight = join_highlights(path_nodes.clone(), highlight_found(&found));
+                // new nodes stay small until the stream settles
+                if let Some(ref mut sizes) = appearance.sizes {
+                    sizes.resize(mapped_graph.graph.node_count(), 0.);
+                }
+                if neighbourhood.is_none() {
+                    neighbourhood = ego(options, &mapped_graph).unwrap_or(None);
+                }
+                let known = types.types.len();
+                types.update(&mapped_graph.graph);
+                for t in types.types[known..].iter_mut() {
+                    t.2 = !options.hide.contains(&t.0);
+                }
+                appearance.visible = shown(&mapped_graph.graph, &filter, neighbourhood.as_ref(), &types);
+                nodes = build_nodes(&display, &mapped_graph.graph, &config.style, &appearance);
+                edges = group_edges(&mapped_graph.graph, &config.style, merged, &appearance);
+                multiplicities = geometry::multiplicities(&mapped_graph.graph);
+                layout.restart();
+                grew = Some(Instant::now());
+            }
+        }
+
+        // what depends on the whole graph, once the stream stops growing for a while
+        if grew.map_or(false, |t| t.elapsed() >= Duration::from_millis(STREAM_SETTLE)) {
+            grew = None;
+            mark_communities(communities, options.resolution, &mut mapped_graph.graph, &mut rng);
+            appearance.sizes = measure(centrality, &mapped_graph.graph, options.samples, &mut rng);
+            limits = Filter::limits(&mapped_graph.graph);
+            nodes = build_nodes(&display, &mapped_graph.graph, &config.style, &appearance);
+            if statistics.is_some() {
+                toggle_statistics = true;
+                statistics = None;
+            }
+        }
+
+        let ref mut graph = mapped_graph.graph;
+
+        if cycle_centrality {
+            cycle_centrality = false;
+            centrality = match centrality {
+                None => Some(centrality::ALL[0]),
+                Some(c) => centrality::ALL.iter().position(|&a| a == c).and_then(|i| centrality::ALL.get(i + 1).cloned()),
+            };
+            match centrality {
+                Some(c) => println!("Nodes sized by {} centrality", c.name()),
+                None => println!("Nodes at their usual size"),
+            }
+            appearance.sizes = measure(centrality, graph, options.samples, &mut rng);
+            nodes = build_nodes(&display, graph, &config.style, &appearance);
+        }
+
+        // the keys step the filter, the sliders set it
+        let slid = {
+            let ui = &mut ui.set_widgets();
+            let slid = filter_sliders(ui, &ids, filter, limits, &config.style);
+            // the checkboxes hang below the sliders
+            if show_interactions {
+                if let Some(i) = type_checkboxes(ui, &mut ids, &types, &config.keys, &config.style) {
+                    toggle_type = Some(i);
+                }
+            }
+            slid
+        };
+        let stepped = step_filter.take().map(|step| {
+            let mut stepped = filter;
+            {
+                let (value, step) = match step {
+                    Action::Core(step) => (&mut stepped.core, step),
+                    Action::Degree(step) => (&mut stepped.degree, step),
+                    _ => unreachable!(),
+                };
+                *value = (*value as isize + step).max(0) as usize;
+            }
+            stepped
+        });
+        if let Some(changed) = stepped.or(slid) {
+            filter = changed;
+            appearance.visible = shown(graph, &filter, neighbourhood.as_ref(), &types);
+            let (shown, _) = appearance.visible.as_ref().map_or((graph.node_count(), 0), |v| v.counts());
+            println!("Showing {}: {} of {} nodes", filter.describe(), shown, graph.node_count());
+            nodes = build_nodes(&display, graph, &config.style, &appearance);
+            edges = group_edges(graph, &config.style, merged, &appearance);
+        }
+
+        if let Some(i) = toggle_type.take() {
+            match types.toggle(i) {
+                Some(true) => println!("Showing {} edges", types.types[i].0),
+                Some(false) => println!("Hiding {} edges", types.types[i].0),
+                None => println!("No interaction type {}", i + 1),
+            }
+            appearance.visible = shown(graph, &filter, neighbourhood.as_ref(), &types);
+            nodes = build_nodes(&display, graph, &config.style, &appearance);
+            edges = group_edges(graph, &config.style, merged, &appearance);
+        }
+
+        if run_query {
+            run_query = false;
+            match query.take().map(|text| Pattern::parse(&text)) {
+                Some(Ok(pattern)) => {
+                    found = pattern.find(graph);
+                    current = None;
+                    match found.len() {
+                        0 => println!("No node matches {}", pattern),
+                        n => {
+                            println!("Nodes matching {}: {}", pattern, n);
+                            step_found = Some(1);
+                        },
+                    }
+                    searched = Some(pattern);
+                    appearance.highlight = join_highlights(path_nodes.clone(), highlight_found(&found));
+                    nodes = build_nodes(&display, graph, &config.style, &appearance);
+                    edges = group_edges(graph, &config.style, merged, &appearance);
+                },
+                Some(Err(e)) => println!("{}", e),
+                None => (),
+            }
+        }
+
+        if let Some(step) = step_found.take() {
+            if found.is_empty() {
+                println!("No nodes found to step through");
+            } else {
+                let count = found.len() as isize;
+                let i = match current {
+                    Some(i) => (((i as isize + step) % count + count) % count) as usize,
+                    None if step > 0 => 0,
+                    None => found.len() - 1,
+                };
+                current = Some(i);
+                // centre the node, zooming in if need be
+                let v = found[i];
+                scale = scale.max(FOUND_SCALE);
+                let zoom = scale.powf(scale);
+                translation.x = -graph[v].pos.x * zoom;
+                translation.y = -graph[v].pos.y * zoom;
+                let hidden = if appearance.shows_node(v) { "" } else { ", hidden" };
+                println!("{} ({} of {}{})", graph[v].name, i + 1, found.len(), hidden);
+            }
+        }
+
+        if toggle_interactions {
+            toggle_interactions = false;
+            show_interactions = !show_interactions;
+        }
+
+        if toggle_communities {
+            toggle_communities = false;
+            communities = match communities {
+                Some(_) => None,
+                None => Some(options.communities.unwrap_or(Method::Leiden)),
+            };
+            mark_communities(communities, options.resolution, graph, &mut rng);
+            match communities {
+                Some(method) => {
+                    let count = graph.node_indices().filter_map(|v| graph[v].community).max().map_or(0, |c| c + 1);
+                    println!("{} communities by {}", count, method.name());
+                },
+                None => println!("Communities hidden"),
+            }
+            nodes = build_nodes(&display, graph, &config.style, &appearance);
+            if config.layout.community_attraction > 0. {
+                layout.restart();
+            }
+        }
+
+        let camera = draw::Camera {
+            width: w,
+            height: h,
+            zoom: scale.powf(scale),
+            translation: Vector2::new(translation.x, translation.y),
+        };
+
+        if let Some(extension) = save {
+            save = None;
+            let mut drawing = draw::Drawing::new(graph, &config.style, merged, &camera, false);
+            appearance.colour(&mut drawing, &config.style);
+            // beside the input rather than over it, which may have the same extension
+            match beside(&filename, &format!("layout.{}", extension)) {
+                Ok(out) => match io::save(graph, &config.style, &drawing, &out) {
+                    Ok(()) => println!("Saved layout to {}", out.display()),
+                    Err(e) => println!("Failed to save {}: {}", out.display(), e),
+                },
+                Err(e) => println!("{}", e),
+            }
+        }
+
+        if save_shown {
+            save_shown = false;
+            let part = match appearance.visible {
+                Some(ref visible) => graph::part(graph, &visible.nodes, &visible.edges),
+                None => graph.clone(),
+            };
+            let mut drawing = draw::Drawing::new(graph, &config.style, merged, &camera, false);
+            appearance.apply(&mut drawing, &config.style);
+            match beside(&filename, "part.cyjs") {
+                Ok(out) => match io::save(&part, &config.style, &drawing, &out) {
+                    Ok(()) => println!("Saved the {} nodes and {} edges shown to {}", part.node_count(), part.edge_count(), out.display()),
+                    Err(e) => println!("Failed to save {}: {}", out.display(), e),
+                },
+                Err(e) => println!("{}", e),
+            }
+        }
+
+        if let Some(change) = change_neighbourhood.take() {
+            let candidates = graph.node_indices().filter(|&v| appearance.shows_node(v));
+            match camera.nearest(graph, candidates, Vector2::new(m_x, m_y), PICK_DISTANCE) {
+                None => println!("No node under the pointer"),
+                Some(v) => {
+                    // expanding with nothing expanded yet starts from that node alone
+                    let mut around = neighbourhood.take().unwrap_or(Ego::new(&[], 0));
+                    let changed = match change {
+                        Action::Expand => {
+                            around.expand(v);
+                            true
+                        },
+                        _ => around.collapse(v),
+                    };
+                    neighbourhood = if around.is_empty() { None } else { Some(around) };
+                    if changed {
+                        appearance.visible = shown(graph, &filter, neighbourhood.as_ref(), &types);
+                        let (count, _) = appearance.visible.as_ref().map_or((graph.node_count(), 0), |visible| visible.counts());
+                        match neighbourhood.as_ref().and_then(|n| n.hops(v)) {
+                            Some(hops) => println!("{} hops around {}: {} of {} nodes", hops, graph[v].name, count, graph.node_count()),
+                            None => println!("Collapsed {}: {} of {} nodes", graph[v].name, count, graph.node_count()),
+                        }
+                        nodes = build_nodes(&display, graph, &config.style, &appearance);
+                        edges = group_edges(graph, &config.style, merged, &appearance);
+                    } else {
+                        println!("{} isn't expanded", graph[v].name);
+                    }
+                }
+            }
+        }
+
+        if let Some(extension) = export {
+            let mut drawing = draw::Drawing::new(graph, &config.style, merged, &camera, true);
+            appearance.apply(&mut drawing, &config.style);
+
+            match beside(&filename, extension) {
+                Ok(out) => {
+                    let result = match extension {
+                        "svg" => export::svg::write_file(&drawing, &out),
+                        "pdf" => export::pdf::write_file(&drawing, &options.page, &out),
+                        _ => export::png::write_file(&drawing, 1., &out),
+                    };
+                    match result {
+                        Ok(()) => println!("Exported view to {}", out.display()),
+                        Err(e) => println!("Failed to export {}: {}", out.display(), e),
+                    }
+                },
+                Err(e) => println!("{}", e),
+            }
+            export = None;
+        }
+
+        if toggle_recording {
+            toggle_recording = false;
+            recording = match recording.take() {
+                Some(finished) => {
+                    println!("Recorded {} frames", finished.frames());
+                    None
+                },
+                None => match beside(&filename, "gif") {
+                    Ok(out) => {
+                        println!("Recording to {}", out.display());
+                        Some(export::record::Recording::new(out, options.fps, 1.))
+                    },
+                    Err(e) => {
+                        println!("{}", e);
+                        None
+                    },
+                },
+            };
+        }
+
+        if toggle_statistics {
+            toggle_statistics = false;
+            statistics = match statistics {
+                Some(_) => None,
+                None => Some(analysis::Statistics::of(graph, analysis::SAMPLES, &mut rng).lines()),
+            };
+        }
+
+        translation.x -= movement.x;
+        translation.y += movement.y;
+        movement.x = 0.;
+        movement.y = 0.;
+
+        let mvp = projection * Matrix4::from_translation(translation) * Matrix4::from_scale(scale.powf(scale));
+        let node_uniforms = uniform! { mvp: mvp.as_uniform() };
+
+        let moved = !layout.is_done();
+        if moved {
+            layout.step(graph);
+            if layout.is_done() {
+                println!("Layout complete!");
+            }
+        }
+
+        if let Some(mut active) = recording.take() {
+            let mut drawing = draw::Drawing::new(graph, &config.style, merged, &camera, true);
+            appearance.apply(&mut drawing, &config.style);
+            match active.frame(&drawing) {
+                Ok(()) if layout.is_done() => println!("Recorded {} frames", active.frames()),
+                Ok(()) => recording = Some(active),
+                Err(e) => println!("Recording failed: {}", e),
+            }
+        }
+
+        {
+            let mut mapping = nodes.map();
+            // zip with nodelist
+            for (node, v) in mapping.iter_mut().zip(graph.node_indices().filter(|&v| appearance.shows_node(v))) {
+                let pos = graph[v].pos;
+
+                node.offset[0] = pos.x;
+                node.offset[1] = pos.y;
+            }
+        }
+
+        square = core.window.with_display(gl::base::make_square).expect("Failed making a triangle!");
+        {
+            let mut mapping = square.map();
+            // zip with nodelist
+            for vertex in mapping.iter_mut() {
+                let scalesq = scale.powf(scale);
+                vertex.position[0] /= scalesq;
+                vertex.position[1] /= scalesq;
+                vertex.position[2] /= scalesq;
+            }
+        }
+
+        let mut frame = display.draw();
+
+        let [r, g, b, a] = config.style.background;
+        frame.clear_color(r, g, b, a);
+        // loops keep a constant size on screen, like the nodes
+        let loop_radius = draw::LOOP_RADIUS / scale.powf(scale);
+        if moved || edges.traced != Some(loop_radius) {
+            edges.trace(&display, graph, &multiplicities, loop_radius);
+        }
+        // a failure ends the view, once the frame is finished
+        let mut drawn = Ok(());
+        for (&(colour, _), segments) in edges.groups.iter().zip(&edges.segments) {
+            let edge_uniforms = uniform! { mvp: mvp.as_uniform(), rgba: colour };
+            drawn = frame.draw(segments, &lines, &line_program, &edge_uniforms, &lineparams);
+            if drawn.is_err() {
+                break;
+            }
+        }
+        if nodes.len() > 0 {
+            frame.draw((&square, nodes.per_instance().unwrap()), &indices, &node_program, &node_uniforms, &Default::default()).unwrap();
+        }
+
+        let mut panel = statistics.clone().unwrap_or_default();
+        if let Some(ref text) = query {
+            panel.push(format!("find: {}_", text));
+        }
+        // text down the left, over the network
+        for (i, line) in panel.iter().enumerate() {
+            let at = Vector3::new(TEXT_MARGIN - w/2., h/2. - LINE_HEIGHT * (i + 1) as f32, 0.);
+            let mvp = projection * Matrix4::from_translation(at) * Matrix4::from_scale(TEXT_SIZE);
+            text.print(line, &mut frame, &mvp, config.style.label);
+        }
+
+        renderer.fill(&display, ui.draw(), &image_map);
+        let controls = renderer.draw(&display, &mut frame, &image_map);
+        frame.finish().unwrap();
+        drawn.map_err(|e| format!("Failed drawing edges: {}", e))?;
+        controls.map_err(|e| format!("Failed drawing the controls: {}", e))?;
+
+        if shutdown {
+            break;
+        }
+    }
+
+    Ok(())
+}